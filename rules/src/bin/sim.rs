@@ -0,0 +1,155 @@
+//! Plays many headless runs to judge balance, e.g.
+//!
+//! ```text
+//! cargo run --release -p glitchbomb-rules --bin sim -- --games 100000 --strategy milestone --mode endless
+//! ```
+//!
+//! Takes `--games`, `--threads`, `--strategy` (pull, milestone), `--mode` (classic, blitz,
+//! endless), `--max-pulls` before a run is abandoned, `--seed` for a reproducible batch,
+//! and `--format` (text, csv). Rules come from `--rules` / `rules.json` and the usual
+//! per-field flags, as in the game.
+
+use std::process::ExitCode;
+use std::thread;
+
+use glitchbomb_rules::{find_flag_value, mix64, simulate_run, GameMode, RulesConfig, SimReport, Strategy};
+
+const DEFAULT_GAMES: u64 = 10_000;
+/// Far more than any run that can end takes, so only runs that never would are cut short.
+const DEFAULT_MAX_PULLS: u64 = 100_000;
+
+struct Options {
+    games: u64,
+    threads: u64,
+    strategy: Strategy,
+    mode: GameMode,
+    max_pulls: usize,
+    seed: u64,
+    csv: bool,
+    rules: RulesConfig,
+}
+
+impl Options {
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let flag = |name: &str| find_flag_value(args, name).map_err(|err| err.to_string());
+        let number = |name: &str, default: u64| -> Result<u64, String> {
+            match flag(name)? {
+                Some(value) => value.parse().map_err(|_| format!("invalid value {:?} for {}", value, name)),
+                None => Ok(default),
+            }
+        };
+
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get() as u64);
+        let mode = match flag("--mode")?.as_deref() {
+            None | Some("classic") => GameMode::Classic,
+            Some("blitz") => GameMode::Blitz,
+            Some("endless") => GameMode::Endless,
+            Some(other) => return Err(format!("unknown mode {:?}; use classic, blitz or endless", other)),
+        };
+        let csv = match flag("--format")?.as_deref() {
+            None | Some("text") => false,
+            Some("csv") => true,
+            Some(other) => return Err(format!("unknown format {:?}; use text or csv", other)),
+        };
+
+        Ok(Self {
+            games: number("--games", DEFAULT_GAMES)?,
+            threads: number("--threads", threads)?.max(1),
+            strategy: flag("--strategy")?.map_or(Ok(Strategy::default()), |name| name.parse())?,
+            mode,
+            max_pulls: number("--max-pulls", DEFAULT_MAX_PULLS)? as usize,
+            seed: number("--seed", rand::random())?,
+            csv,
+            rules: RulesConfig::from_args(args.iter().cloned()).map_err(|err| err.to_string())?,
+        })
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match Options::from_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("sim: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = run(&options);
+    if options.csv {
+        print_csv(&report);
+    } else {
+        print_text(&options, &report);
+    }
+    ExitCode::SUCCESS
+}
+
+/// Game `i` always gets the same seed for a given `--seed`, however the games are split
+/// across threads.
+fn run(options: &Options) -> SimReport {
+    let per_thread = options.games.div_ceil(options.threads);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.threads)
+            .map(|thread| {
+                let games = (thread * per_thread)..((thread + 1) * per_thread).min(options.games);
+                scope.spawn(move || {
+                    let mut report = SimReport::default();
+                    for game in games {
+                        let seed = mix64(options.seed.wrapping_add(game));
+                        report.add(simulate_run(&options.rules, options.mode, options.strategy, options.max_pulls, game + 1, seed));
+                    }
+                    report
+                })
+            })
+            .collect();
+
+        workers.into_iter().fold(SimReport::default(), |mut total, worker| {
+            total.merge(worker.join().expect("simulation thread panicked"));
+            total
+        })
+    })
+}
+
+fn print_text(options: &Options, report: &SimReport) {
+    println!(
+        "{} {} games, {} strategy, {} threads, seed {}",
+        report.games,
+        options.mode.name(),
+        options.strategy,
+        options.threads,
+        options.seed,
+    );
+    if options.mode == GameMode::Blitz {
+        println!("Blitz timers aren't simulated, so no run times out.");
+    }
+    println!();
+    println!("Win rate:        {:.1}%", report.win_rate() * 100.0);
+    println!("Average points:  {:.1}", report.average_score());
+    println!("Average pulls:   {:.1}", report.average_pulls());
+
+    println!("\nLevel reached:");
+    for (level, count) in &report.levels {
+        println!("  {:>4}  {:>8}  {:>5.1}%", level, count, report.share(*count) * 100.0);
+    }
+
+    println!("\nEndings:");
+    for (ending, count) in &report.endings {
+        println!("  {:<16}  {:>8}  {:>5.1}%", ending.name(), count, report.share(*count) * 100.0);
+    }
+}
+
+/// One `metric,value` row per figure, for pasting into a spreadsheet.
+fn print_csv(report: &SimReport) {
+    println!("metric,value");
+    println!("games,{}", report.games);
+    println!("win_rate,{:.4}", report.win_rate());
+    println!("average_points,{:.2}", report.average_score());
+    println!("average_pulls,{:.2}", report.average_pulls());
+    for (level, count) in &report.levels {
+        println!("level_{},{}", level, count);
+    }
+    for (ending, count) in &report.endings {
+        println!("ending_{},{}", ending.name().to_lowercase().replace(' ', "_"), count);
+    }
+}
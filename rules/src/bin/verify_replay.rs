@@ -0,0 +1,74 @@
+//! Re-simulates replay files without opening a window and checks each one reaches the
+//! result it claims. Exits with 1 if any replay fails, or 2 if one can't be read.
+//!
+//! ```text
+//! cargo run -p glitchbomb-rules --bin verify_replay -- replay-42-9F3A1C2B.json [more.json ...]
+//! ```
+//!
+//! Replays don't carry their rules, so they're checked against the rules the game would
+//! use: `--rules` / `rules.json` and the usual per-field flags.
+
+use std::fs;
+use std::process::ExitCode;
+
+use glitchbomb_rules::{Replay, RulesConfig};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let rules = match RulesConfig::from_args(args.iter().cloned()) {
+        Ok(rules) => rules,
+        Err(err) => {
+            eprintln!("verify_replay: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let paths = replay_paths(&args);
+    if paths.is_empty() {
+        eprintln!("usage: verify_replay [rule flags] <replay.json>...");
+        return ExitCode::from(2);
+    }
+
+    let (mut failed, mut unreadable) = (0, 0);
+    for path in &paths {
+        let replay = match fs::read(path).map_err(|err| err.to_string()).and_then(|contents| Replay::from_json(&contents)) {
+            Ok(replay) => replay,
+            Err(err) => {
+                println!("{}: ERROR {}", path, err);
+                unreadable += 1;
+                continue;
+            }
+        };
+
+        match replay.verify(&rules) {
+            Ok(result) => println!("{}: OK game {} ({} actions): {}", path, replay.game_id, replay.len(), result),
+            Err(err) => {
+                println!("{}: FAIL game {}: {}", path, replay.game_id, err);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("{} verified, {} failed, {} unreadable", paths.len() - failed - unreadable, failed, unreadable);
+    if unreadable > 0 {
+        ExitCode::from(2)
+    } else if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Everything that isn't a flag or a flag's value.
+fn replay_paths(args: &[String]) -> Vec<&String> {
+    let mut paths = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            paths.push(arg);
+        } else if !arg.contains('=') {
+            iter.next();
+        }
+    }
+    paths
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use super::config::RulesConfig;
+use super::state::RunState;
+
+/// Timers for blitz mode, in seconds. `None` turns that timer off.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlitzConfig {
+    /// Time to reach each level's milestone before the run is lost.
+    pub level_seconds: Option<f32>,
+    /// Time allowed between pulls before one is made automatically.
+    pub pull_seconds: Option<f32>,
+}
+
+impl Default for BlitzConfig {
+    fn default() -> Self {
+        Self { level_seconds: Some(30.0), pull_seconds: Some(4.0) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlitzClock {
+    pub level_remaining: Option<f32>,
+    pub pull_remaining: Option<f32>,
+}
+
+impl BlitzClock {
+    pub fn new(config: &BlitzConfig) -> Self {
+        Self { level_remaining: config.level_seconds, pull_remaining: config.pull_seconds }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.level_remaining.is_some_and(|remaining| remaining <= 0.0)
+    }
+}
+
+impl RunState {
+    /// Advances the blitz clock. Returns `true` when the pull timer ran out and
+    /// an automatic pull is due.
+    pub fn tick(&mut self, seconds: f32, rules: &RulesConfig) -> bool {
+        if !self.can_pull() {
+            return false;
+        }
+        let Some(clock) = &mut self.clock else {
+            return false;
+        };
+
+        if let Some(remaining) = &mut clock.level_remaining {
+            *remaining = (*remaining - seconds).max(0.0);
+        }
+
+        let Some(remaining) = &mut clock.pull_remaining else {
+            return false;
+        };
+        *remaining -= seconds;
+        if *remaining > 0.0 {
+            return false;
+        }
+
+        *remaining = rules.blitz.pull_seconds.unwrap_or_default();
+        true
+    }
+
+    /// Puts the pull timer back to full after the player pulls.
+    pub fn reset_pull_timer(&mut self, rules: &RulesConfig) {
+        if let Some(clock) = &mut self.clock {
+            clock.pull_remaining = rules.blitz.pull_seconds;
+        }
+    }
+
+    pub fn reset_clock(&mut self, rules: &RulesConfig) {
+        if let Some(clock) = &mut self.clock {
+            *clock = BlitzClock::new(&rules.blitz);
+        }
+    }
+}
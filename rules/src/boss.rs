@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use super::config::BagComposition;
+use super::objectives::Reward;
+
+/// A named rule change, applied on a boss level or across a whole daily challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BossModifier {
+    DoubleBombDamage,
+    NoHealing,
+    HiddenMilestone,
+}
+
+impl BossModifier {
+    pub const ALL: [BossModifier; 3] =
+        [BossModifier::DoubleBombDamage, BossModifier::NoHealing, BossModifier::HiddenMilestone];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            BossModifier::DoubleBombDamage => "Bombs deal double damage",
+            BossModifier::NoHealing => "Health orbs are disabled",
+            BossModifier::HiddenMilestone => "The milestone is hidden",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossLevel {
+    pub name: String,
+    pub modifier: BossModifier,
+    /// Orbs added on top of the starting bag for this level.
+    pub extra_orbs: BagComposition,
+    pub reward: Reward,
+}
+
+/// Every `every`th level is a boss level; bosses are used in order and then repeat.
+/// `every: 0` turns boss levels off. Classic is a single level by default, so the
+/// default spacing only brings bosses into endless runs and runs with more levels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BossConfig {
+    pub every: u32,
+    pub bosses: Vec<BossLevel>,
+}
+
+impl Default for BossConfig {
+    fn default() -> Self {
+        Self {
+            every: 5,
+            bosses: vec![
+                BossLevel {
+                    name: "The Detonator".to_string(),
+                    modifier: BossModifier::DoubleBombDamage,
+                    extra_orbs: BagComposition { health: 0, point: 1, bomb: 1 },
+                    reward: Reward::Moonrocks(5),
+                },
+                BossLevel {
+                    name: "The Drought".to_string(),
+                    modifier: BossModifier::NoHealing,
+                    extra_orbs: BagComposition { health: 0, point: 2, bomb: 2 },
+                    reward: Reward::Moonrocks(5),
+                },
+                BossLevel {
+                    name: "The Fog".to_string(),
+                    modifier: BossModifier::HiddenMilestone,
+                    extra_orbs: BagComposition { health: 0, point: 1, bomb: 2 },
+                    reward: Reward::Moonrocks(5),
+                },
+            ],
+        }
+    }
+}
+
+impl BossConfig {
+    pub fn boss_for_level(&self, level: u32) -> Option<&BossLevel> {
+        if self.every == 0 || self.bosses.is_empty() || !level.is_multiple_of(self.every) {
+            return None;
+        }
+
+        let index = (level / self.every - 1) as usize % self.bosses.len();
+        self.bosses.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RulesConfig;
+    use crate::mode::GameMode;
+    use crate::replay::ReplayAction;
+    use crate::state::RunState;
+
+    #[test]
+    fn endless_runs_reach_a_boss_level_by_default() {
+        let rules = RulesConfig { starting_health: 1000, max_health: 1000, ..RulesConfig::default() };
+        let mut state = RunState::new_run(&rules, GameMode::Endless, 1, 7);
+        while !state.is_boss_level(&rules) {
+            assert!(state.can_pull() && state.level <= rules.boss.every, "no boss by level {}", state.level);
+            state.apply(ReplayAction::Pull, &rules);
+        }
+        assert_eq!(state.level, rules.boss.every);
+        assert!(RunState::new_run(&rules, GameMode::Classic, 1, 7).final_level < Some(rules.boss.every));
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use super::blitz::BlitzConfig;
+use super::mode::{EndlessConfig, GameMode};
+use super::boss::{BossConfig, BossLevel, BossModifier};
+use super::fnv::Fnv1a;
+use super::objectives::{default_objectives, Objective};
+use super::orb::Orb;
+
+/// Rules file picked up from the working directory when `--rules` isn't given.
+pub const DEFAULT_RULES_PATH: &str = "rules.json";
+
+/// Every balance number the game reads, so designers can tweak a run
+/// without recompiling. Missing fields in a rules file fall back to the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RulesConfig {
+    pub starting_health: u32,
+    pub max_health: u32,
+    pub milestone: u32,
+    /// How much the milestone rises with each level after the first.
+    pub milestone_step: u32,
+    /// Completing this level wins the run. Classic is a single level unless raised.
+    pub levels: u32,
+    pub point_value: u32,
+    pub bomb_damage: u32,
+    pub heal_amount: u32,
+    pub starting_bag: BagComposition,
+    pub objectives: Vec<Objective>,
+    pub boss: BossConfig,
+    pub blitz: BlitzConfig,
+    pub endless: EndlessConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BagComposition {
+    pub health: u32,
+    pub point: u32,
+    pub bomb: u32,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, serde_json::Error),
+    MissingValue(String),
+    InvalidValue(String, String),
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        Self {
+            starting_health: 5,
+            max_health: 5,
+            milestone: 15,
+            milestone_step: 5,
+            levels: 1,
+            point_value: 5,
+            bomb_damage: 2,
+            heal_amount: 1,
+            starting_bag: BagComposition::default(),
+            objectives: default_objectives(),
+            boss: BossConfig::default(),
+            blitz: BlitzConfig::default(),
+            endless: EndlessConfig::default(),
+        }
+    }
+}
+
+impl Default for BagComposition {
+    fn default() -> Self {
+        Self { health: 5, point: 5, bomb: 5 }
+    }
+}
+
+impl BagComposition {
+    pub fn total(&self) -> u32 {
+        self.health + self.point + self.bomb
+    }
+
+    /// Builds the bag interleaved the same way the original hard-coded bag was.
+    pub fn to_orbs(self) -> Vec<Orb> {
+        let mut orbs = Vec::with_capacity(self.total() as usize);
+        let rounds = self.health.max(self.point).max(self.bomb);
+
+        for round in 0..rounds {
+            if round < self.health {
+                orbs.push(Orb::Health);
+            }
+            if round < self.point {
+                orbs.push(Orb::Point);
+            }
+            if round < self.bomb {
+                orbs.push(Orb::Bomb);
+            }
+        }
+
+        orbs
+    }
+}
+
+impl RulesConfig {
+    pub fn milestone_for_level(&self, mode: GameMode, level: u32) -> u32 {
+        let milestone = self.milestone + self.milestone_step * level.saturating_sub(1);
+
+        match mode {
+            GameMode::Endless => milestone + self.endless.extra_milestone(level),
+            _ => milestone,
+        }
+    }
+
+    /// The level that wins the run, or `None` when the run only ends on death.
+    pub fn final_level(&self, mode: GameMode) -> Option<u32> {
+        match mode {
+            GameMode::Endless => None,
+            _ => Some(self.levels),
+        }
+    }
+
+    /// Identifies these exact rules, so a replay can name the rules it was recorded
+    /// under without carrying a copy.
+    pub fn fingerprint(&self) -> u32 {
+        let mut hash = Fnv1a::new();
+        hash.write(&serde_json::to_vec(self).expect("rules always serialize"));
+        hash.finish_u32()
+    }
+
+    pub fn boss_for_level(&self, level: u32) -> Option<&BossLevel> {
+        self.boss.boss_for_level(level)
+    }
+
+    pub fn has_modifier(&self, level: u32, modifier: BossModifier) -> bool {
+        self.boss_for_level(level).is_some_and(|boss| boss.modifier == modifier)
+    }
+
+    /// The bag a level starts with, including any boss or endless injection.
+    pub fn bag_for_level(&self, mode: GameMode, level: u32) -> BagComposition {
+        let mut bag = self.starting_bag;
+        if mode == GameMode::Endless {
+            // One point orb for every `point_value` the milestone has grown, so the bag
+            // always holds as much slack over the milestone as the first level.
+            let growth = self.milestone_for_level(mode, level) - self.milestone;
+            bag.point += growth.div_ceil(self.point_value.max(1));
+            bag.bomb += self.endless.extra_bombs(level);
+        }
+        if let Some(boss) = self.boss_for_level(level) {
+            bag.health += boss.extra_orbs.health;
+            bag.point += boss.extra_orbs.point;
+            bag.bomb += boss.extra_orbs.bomb;
+        }
+        bag
+    }
+
+    pub fn objectives_for_level(&self, level: u32) -> impl Iterator<Item = &Objective> {
+        self.objectives.iter().filter(move |objective| objective.applies_to(level))
+    }
+
+    /// Loads the rules from the file named by `--rules` (or `rules.json` if it exists),
+    /// then applies any per-field command line overrides on top.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, ConfigError> {
+        let args: Vec<String> = args.into_iter().collect();
+
+        let rules_path = match find_flag_value(&args, "--rules")? {
+            Some(path) => Some(PathBuf::from(path)),
+            None => {
+                let default_path = PathBuf::from(DEFAULT_RULES_PATH);
+                default_path.exists().then_some(default_path)
+            }
+        };
+
+        let mut config = match rules_path {
+            Some(path) => Self::load(&path)?,
+            None => Self::default(),
+        };
+
+        config.apply_overrides(&args)?;
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+
+        serde_json::from_str(&contents)
+            .map_err(|err| ConfigError::Parse(path.to_path_buf(), err))
+    }
+
+    /// Applies `--starting-health 7` / `--bomb-damage=3` style overrides.
+    /// Arguments that aren't rule flags are left for whoever else reads the command line.
+    pub fn apply_overrides(&mut self, args: &[String]) -> Result<(), ConfigError> {
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if !arg.starts_with("--") {
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+
+            let field = match flag {
+                "--starting-health" => &mut self.starting_health,
+                "--max-health" => &mut self.max_health,
+                "--milestone" => &mut self.milestone,
+                "--milestone-step" => &mut self.milestone_step,
+                "--levels" => &mut self.levels,
+                "--boss-every" => &mut self.boss.every,
+                "--point-value" => &mut self.point_value,
+                "--bomb-damage" => &mut self.bomb_damage,
+                "--heal-amount" => &mut self.heal_amount,
+                "--bag-health" => &mut self.starting_bag.health,
+                "--bag-point" => &mut self.starting_bag.point,
+                "--bag-bomb" => &mut self.starting_bag.bomb,
+                _ => continue,
+            };
+
+            let value = match inline_value {
+                Some(value) => value,
+                None => iter.next().cloned().ok_or_else(|| ConfigError::MissingValue(flag.to_string()))?,
+            };
+
+            *field = value
+                .parse()
+                .map_err(|_| ConfigError::InvalidValue(flag.to_string(), value.clone()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds `--flag value` or `--flag=value` on the command line.
+pub fn find_flag_value(args: &[String], flag: &str) -> Result<Option<String>, ConfigError> {
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            return iter
+                .next()
+                .cloned()
+                .map(Some)
+                .ok_or_else(|| ConfigError::MissingValue(flag.to_string()));
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+            return Ok(Some(value.to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "could not read rules file {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "invalid rules file {}: {}", path.display(), err),
+            ConfigError::MissingValue(flag) => write!(f, "missing value for {}", flag),
+            ConfigError::InvalidValue(flag, value) => write!(f, "invalid value {:?} for {}", value, flag),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,60 @@
+use chrono::{Datelike, Local, NaiveDate};
+
+use super::boss::BossModifier;
+use super::game_id::mix64;
+
+/// Keeps daily seeds apart from anything else derived from a bare day number.
+const DAILY_SALT: u64 = 0x6762_6461_696C_7921;
+
+/// The challenge for one calendar date. Everything is derived from the date alone,
+/// so every player gets the same bag order and modifier on the same day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyChallenge {
+    pub date: NaiveDate,
+    pub seed: u64,
+    pub modifier: BossModifier,
+}
+
+impl DailyChallenge {
+    pub fn for_date(date: NaiveDate) -> Self {
+        let seed = mix64(date.num_days_from_ce() as u64 ^ DAILY_SALT);
+        let modifier = BossModifier::ALL[(mix64(seed) % BossModifier::ALL.len() as u64) as usize];
+
+        Self { date, seed, modifier }
+    }
+
+    /// Today's challenge by the local calendar.
+    pub fn today() -> Self {
+        Self::for_date(Local::now().date_naive())
+    }
+
+    /// `YYYY-MM-DD`, used to look the day up in the profile.
+    pub fn key(&self) -> String {
+        self.date.format("%Y-%m-%d").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Every player's game derives the day's challenge on its own, so this must never change.
+    #[test]
+    fn daily_seeds_are_pinned() {
+        let daily = DailyChallenge::for_date(date(2026, 1, 1));
+        assert_eq!(daily.seed, 0x0D8F_319F_C1BD_44C8);
+        assert_eq!(daily.modifier, BossModifier::NoHealing);
+        assert_eq!(daily.key(), "2026-01-01");
+    }
+
+    #[test]
+    fn each_day_gets_its_own_seed() {
+        let first = DailyChallenge::for_date(date(2026, 3, 14));
+        assert_eq!(DailyChallenge::for_date(date(2026, 3, 14)), first);
+        assert_ne!(DailyChallenge::for_date(date(2026, 3, 15)).seed, first.seed);
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Identifies one run: the player's run number plus a hash of the run's seed,
+/// shown as e.g. `42-9F3A1C2B` so bug reports and leaderboards can name a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameId {
+    pub run_number: u64,
+    pub seed_hash: u32,
+}
+
+impl GameId {
+    pub fn new(run_number: u64, seed: u64) -> Self {
+        Self { run_number, seed_hash: seed_hash(seed) }
+    }
+}
+
+impl Default for GameId {
+    fn default() -> Self {
+        Self::new(1, 0)
+    }
+}
+
+impl fmt::Display for GameId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:08X}", self.run_number, self.seed_hash)
+    }
+}
+
+/// SplitMix64 finaliser folded down to 32 bits; stable across platforms and builds.
+pub fn seed_hash(seed: u64) -> u32 {
+    let z = mix64(seed);
+    (z ^ (z >> 32)) as u32
+}
+
+/// SplitMix64 finaliser: spreads nearby inputs (run numbers, dates) across the whole range.
+pub fn mix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
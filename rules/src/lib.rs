@@ -0,0 +1,35 @@
+//! The game rules with no Bevy dependency. The game's `game_state` plugin is a thin
+//! adapter over this crate, so simulations, servers and tests can use it without the engine.
+
+pub mod blitz;
+pub mod boss;
+pub mod config;
+pub mod daily;
+mod fnv;
+pub mod game_id;
+pub mod mode;
+pub mod objectives;
+pub mod odds;
+pub mod orb;
+pub mod outcome;
+pub mod replay;
+pub mod seed_code;
+pub mod sim;
+pub mod solver;
+pub mod state;
+
+pub use blitz::*;
+pub use boss::*;
+pub use config::*;
+pub use daily::*;
+pub use game_id::*;
+pub use mode::*;
+pub use objectives::*;
+pub use odds::*;
+pub use orb::*;
+pub use outcome::*;
+pub use replay::*;
+pub use seed_code::*;
+pub use sim::*;
+pub use solver::*;
+pub use state::*;
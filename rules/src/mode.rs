@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    /// Levels are played against a countdown, and slow pulls are made for you.
+    Blitz,
+    /// No final level: milestones and bombs keep climbing until the player dies.
+    Endless,
+    /// Classic rules with a seed and modifier shared by everyone playing on the same date.
+    Daily,
+}
+
+impl GameMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Blitz => "Blitz",
+            GameMode::Endless => "Endless",
+            GameMode::Daily => "Daily",
+        }
+    }
+}
+
+/// How endless mode escalates. With `level` counted from 1:
+/// milestone grows by `milestone_acceleration * (level - 1) * (level - 2) / 2` on top of the
+/// normal per-level step, and `(level - 1) * level / (2 * bomb_ramp)` extra bombs are added.
+/// Point orbs grow with the milestone and the bag refills whenever it runs dry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndlessConfig {
+    pub milestone_acceleration: u32,
+    pub bomb_ramp: u32,
+}
+
+impl Default for EndlessConfig {
+    fn default() -> Self {
+        Self { milestone_acceleration: 1, bomb_ramp: 2 }
+    }
+}
+
+impl EndlessConfig {
+    pub fn extra_milestone(&self, level: u32) -> u32 {
+        let steps = level.saturating_sub(1);
+        self.milestone_acceleration * steps * steps.saturating_sub(1) / 2
+    }
+
+    pub fn extra_bombs(&self, level: u32) -> u32 {
+        level.saturating_sub(1) * level / (2 * self.bomb_ramp.max(1))
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use super::orb::Orb;
+use super::state::RunState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectiveKind {
+    /// Complete the level with at least this much health.
+    MinHealth(u32),
+    /// Reach the milestone using fewer pulls than this.
+    UnderPulls(u32),
+    /// Complete the level without pulling a single Bomb.
+    NoBombs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Reward {
+    Cheddah(u32),
+    Moonrocks(u32),
+}
+
+/// An optional goal for a level. `level: None` applies it to every level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Objective {
+    pub kind: ObjectiveKind,
+    pub reward: Reward,
+    #[serde(default)]
+    pub level: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectiveStatus {
+    OnTrack,
+    Behind,
+    Failed,
+}
+
+impl Objective {
+    pub fn new(kind: ObjectiveKind, reward: Reward) -> Self {
+        Self { kind, reward, level: None }
+    }
+
+    pub fn applies_to(&self, level: u32) -> bool {
+        self.level.is_none_or(|only| only == level)
+    }
+
+    pub fn description(&self) -> String {
+        let goal = match self.kind {
+            ObjectiveKind::MinHealth(health) => format!("Finish with at least {} health", health),
+            ObjectiveKind::UnderPulls(pulls) => format!("Reach the milestone in under {} pulls", pulls),
+            ObjectiveKind::NoBombs => "Never pull a Bomb".to_string(),
+        };
+        let reward = match self.reward {
+            Reward::Cheddah(amount) => format!("+{} cheddah", amount),
+            Reward::Moonrocks(amount) => format!("+{} moonrocks", amount),
+        };
+
+        format!("{} ({})", goal, reward)
+    }
+
+    /// Live progress so far this level, e.g. `4/3 health` or `5/7 pulls`.
+    pub fn progress(&self, state: &RunState) -> String {
+        match self.kind {
+            ObjectiveKind::MinHealth(health) => format!("{}/{} health", state.health, health),
+            ObjectiveKind::UnderPulls(pulls) => format!("{}/{} pulls", state.level_pull_count(), pulls.saturating_sub(1)),
+            ObjectiveKind::NoBombs => format!("{} bombs", state.level_pulls_of(Orb::Bomb)),
+        }
+    }
+
+    /// Where the objective stands right now. `Failed` can't be recovered this level;
+    /// `Behind` still can, e.g. by healing.
+    pub fn status(&self, state: &RunState) -> ObjectiveStatus {
+        match self.kind {
+            ObjectiveKind::MinHealth(health) if state.health >= health => ObjectiveStatus::OnTrack,
+            ObjectiveKind::MinHealth(_) => ObjectiveStatus::Behind,
+            ObjectiveKind::UnderPulls(pulls) if state.level_pull_count() < pulls as usize => ObjectiveStatus::OnTrack,
+            ObjectiveKind::UnderPulls(_) => ObjectiveStatus::Failed,
+            ObjectiveKind::NoBombs if state.level_pulls_of(Orb::Bomb) == 0 => ObjectiveStatus::OnTrack,
+            ObjectiveKind::NoBombs => ObjectiveStatus::Failed,
+        }
+    }
+
+    /// Only meaningful once the level is complete: everything still on track is achieved.
+    pub fn is_achieved(&self, state: &RunState) -> bool {
+        state.is_level_complete() && self.status(state) == ObjectiveStatus::OnTrack
+    }
+}
+
+pub fn default_objectives() -> Vec<Objective> {
+    vec![
+        Objective::new(ObjectiveKind::MinHealth(3), Reward::Cheddah(5)),
+        Objective::new(ObjectiveKind::UnderPulls(8), Reward::Cheddah(10)),
+        Objective::new(ObjectiveKind::NoBombs, Reward::Moonrocks(1)),
+    ]
+}
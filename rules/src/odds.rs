@@ -0,0 +1,27 @@
+use super::config::RulesConfig;
+use super::state::RunState;
+
+/// What the next pull looks like, derived from the current bag composition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PullOdds {
+    pub bomb_chance: f64,
+    pub lethal_chance: f64,
+    pub expected_points: f64,
+}
+
+impl RunState {
+    /// `None` when the bag is empty and there is no next pull.
+    pub fn next_pull_odds(&self, rules: &RulesConfig) -> Option<PullOdds> {
+        let total = self.total_orb_count();
+        if total == 0 {
+            return None;
+        }
+
+        let total = total as f64;
+        let bomb_chance = self.bomb_orb_count() as f64 / total;
+        let lethal_chance = if self.bomb_damage(rules) >= self.health { bomb_chance } else { 0.0 };
+        let expected_points = self.point_orb_count() as f64 / total * rules.point_value as f64;
+
+        Some(PullOdds { bomb_chance, lethal_chance, expected_points })
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Orb {
+    Health,
+    Point,
+    Bomb,
+}
+
+impl Orb {
+    pub const ALL: [Orb; 3] = [Orb::Health, Orb::Point, Orb::Bomb];
+}
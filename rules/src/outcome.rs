@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LossReason {
+    HealthDepleted,
+    BagEmpty,
+    Timeout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RunOutcome {
+    Won,
+    Lost(LossReason),
+}
+
+impl RunOutcome {
+    pub fn is_win(&self) -> bool {
+        matches!(self, RunOutcome::Won)
+    }
+}
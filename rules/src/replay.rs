@@ -0,0 +1,456 @@
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use super::config::RulesConfig;
+use super::fnv::Fnv1a;
+use super::game_id::GameId;
+use super::objectives::{Objective, Reward};
+use super::orb::Orb;
+use super::outcome::RunOutcome;
+use super::state::RunState;
+
+/// Everything that can change a run apart from the passage of time. Playing back a
+/// run's actions against its initial state reproduces the run exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayAction {
+    Pull,
+    /// The blitz level clock ran out. Recorded instead of the clock ticks themselves,
+    /// which depend on frame timing.
+    Timeout,
+}
+
+impl ReplayAction {
+    fn code(self) -> char {
+        match self {
+            ReplayAction::Pull => 'P',
+            ReplayAction::Timeout => 'T',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            'P' => Some(ReplayAction::Pull),
+            'T' => Some(ReplayAction::Timeout),
+            _ => None,
+        }
+    }
+}
+
+/// What finishing a level paid out. Produced on the pull that reaches the milestone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelReport {
+    pub level: u32,
+    pub points: u32,
+    pub objectives: Vec<Objective>,
+    pub boss_reward: Option<Reward>,
+    /// `false` on the final level, where the run is won instead.
+    pub advanced: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepReport {
+    /// `None` if the action was a pull that wasn't allowed.
+    pub orb: Option<Orb>,
+    /// The pull emptied an endless bag, which was filled again.
+    pub bag_refilled: bool,
+    pub level: Option<LevelReport>,
+}
+
+impl RunState {
+    /// Applies one action. The game's systems and replay playback both go through
+    /// here, so they can't drift apart.
+    pub fn apply(&mut self, action: ReplayAction, rules: &RulesConfig) -> StepReport {
+        match action {
+            ReplayAction::Pull => {
+                if !self.can_pull() {
+                    return StepReport::default();
+                }
+                let orb = self.pull_orb(rules);
+                let bag_refilled = self.refill_bag(rules);
+                StepReport { orb, bag_refilled, level: self.finish_level(rules) }
+            }
+            ReplayAction::Timeout => {
+                if let Some(level_remaining) = self.clock.as_mut().and_then(|clock| clock.level_remaining.as_mut()) {
+                    *level_remaining = 0.0;
+                }
+                StepReport::default()
+            }
+        }
+    }
+
+    /// Claims a completed level's rewards and moves on to the next level, unless it was
+    /// the final one. Does nothing while the level is still open.
+    pub fn finish_level(&mut self, rules: &RulesConfig) -> Option<LevelReport> {
+        if !self.is_level_complete() {
+            return None;
+        }
+
+        let (level, points) = (self.level, self.points);
+        let objectives = self.claim_objectives(rules);
+        let boss_reward = self.claim_boss_reward(rules);
+
+        let advanced = !self.is_final_level();
+        if advanced {
+            self.advance_level(rules);
+        }
+
+        Some(LevelReport { level, points, objectives, boss_reward, advanced })
+    }
+
+    /// A fingerprint of everything replays must reproduce, hashed from an explicit list of
+    /// fields so adding one to `RunState` doesn't invalidate stored replays. The blitz clock
+    /// is left out because it follows frame timing rather than actions.
+    pub fn digest(&self) -> u32 {
+        let mut hash = Fnv1a::new();
+        hash.write_u32(DIGEST_VERSION);
+        for value in [self.health, self.max_health, self.points, self.score, self.level, self.milestone] {
+            hash.write_u32(value);
+        }
+        hash.write_u32(self.moonrocks);
+        hash.write_u32(self.cheddah);
+        hash.write_u64(self.draws);
+        hash.write_u32(self.orbs.len() as u32);
+        for &orb in &self.orbs {
+            hash.write_u8(match orb {
+                Orb::Health => 0,
+                Orb::Point => 1,
+                Orb::Bomb => 2,
+            });
+        }
+        hash.finish_u32()
+    }
+}
+
+/// Bump along with [`Replay::FORMAT_VERSION`] whenever [`RunState::digest`] hashes
+/// different fields, since older digests can't be recomputed.
+pub const DIGEST_VERSION: u32 = 1;
+
+/// A run as its initial state and ordered actions, with the state digest after each
+/// action so playback can prove it stayed in step. The rules aren't stored, only their
+/// [`RulesConfig::fingerprint`], so playing one back needs the same rules passed in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub game_id: GameId,
+    pub rules_hash: u32,
+    pub initial: RunState,
+    /// Stored as one character per action, e.g. `"PPPPT"`.
+    #[serde(serialize_with = "serialize_actions", deserialize_with = "deserialize_actions")]
+    pub actions: Vec<ReplayAction>,
+    pub digests: Vec<u32>,
+    /// How the run ended, written when it finished. `None` for runs still in progress.
+    pub result: Option<ReplayResult>,
+}
+
+/// The end of a run as the player's game reported it, for checking by re-simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayResult {
+    /// Total points across every level.
+    pub points: u32,
+    pub health: u32,
+    pub level: u32,
+    pub outcome: Option<RunOutcome>,
+}
+
+impl ReplayResult {
+    pub fn of(state: &RunState) -> Self {
+        Self { points: state.total_score(), health: state.health, level: state.level, outcome: state.outcome() }
+    }
+}
+
+impl fmt::Display for ReplayResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self.outcome {
+            Some(RunOutcome::Won) => "won".to_string(),
+            Some(RunOutcome::Lost(reason)) => format!("lost ({:?})", reason),
+            None => "unfinished".to_string(),
+        };
+        write!(f, "{} points, {} health, level {}, {}", self.points, self.health, self.level, outcome)
+    }
+}
+
+/// Why a replay failed verification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyError {
+    /// Recorded under rules other than the ones it's being checked against.
+    WrongRules { expected: u32, found: u32 },
+    /// The initial state isn't what a new run with the same seed would start from.
+    WrongInitialState,
+    /// Re-simulating drifted from the recorded digests.
+    Diverged(ReplayMismatch),
+    /// The digests matched but the end result doesn't agree with the claimed one.
+    WrongResult { claimed: ReplayResult, actual: ReplayResult },
+    /// The run was never finished, so there's nothing to check against.
+    NoResult,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::WrongRules { expected, found } => {
+                write!(f, "recorded under rules {:08X}, expected {:08X}", found, expected)
+            }
+            VerifyError::WrongInitialState => write!(f, "the initial state doesn't match a new run with its seed"),
+            VerifyError::Diverged(mismatch) => write!(f, "{}", mismatch),
+            VerifyError::WrongResult { claimed, actual } => write!(f, "claimed {}, but re-simulation gives {}", claimed, actual),
+            VerifyError::NoResult => write!(f, "the replay has no final result"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Where playback first disagreed with the recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayMismatch {
+    /// 0-based index into `actions`.
+    pub step: usize,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+impl fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "state diverged at step {}: expected digest {:08X}, got {:08X}",
+            self.step + 1,
+            self.expected,
+            self.actual
+        )
+    }
+}
+
+impl std::error::Error for ReplayMismatch {}
+
+impl Replay {
+    /// Version of the replay file format, kept under a top-level `version` key so
+    /// replays shared outside the game can be read without the game's save code.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Parses a replay file, refusing ones written by a newer format.
+    pub fn from_json(contents: &[u8]) -> Result<Self, String> {
+        let mut data: Map<String, Value> = serde_json::from_slice(contents).map_err(|err| err.to_string())?;
+        let version = data
+            .remove("version")
+            .ok_or("missing version")?
+            .as_u64()
+            .ok_or("invalid version")?;
+        if version > u64::from(Self::FORMAT_VERSION) {
+            return Err(format!("version {} is newer than the supported {}", version, Self::FORMAT_VERSION));
+        }
+        serde_json::from_value(Value::Object(data)).map_err(|err| err.to_string())
+    }
+
+    pub fn new(rules: &RulesConfig, initial: &RunState) -> Self {
+        Self {
+            game_id: initial.game_id,
+            rules_hash: rules.fingerprint(),
+            initial: initial.clone(),
+            actions: Vec::new(),
+            digests: Vec::new(),
+            result: None,
+        }
+    }
+
+    /// Stores the final state's result once the run is over.
+    pub fn finish(&mut self, state: &RunState) {
+        self.result = Some(ReplayResult::of(state));
+    }
+
+    /// Appends an action that has just been applied to `state`.
+    pub fn record(&mut self, action: ReplayAction, state: &RunState) {
+        self.actions.push(action);
+        self.digests.push(state.digest());
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Checks that `state`, reached after applying `actions[step]`, matches the recording.
+    pub fn check_step(&self, step: usize, state: &RunState) -> Result<(), ReplayMismatch> {
+        let Some(&expected) = self.digests.get(step) else {
+            return Ok(());
+        };
+
+        let actual = state.digest();
+        if actual == expected { Ok(()) } else { Err(ReplayMismatch { step, expected, actual }) }
+    }
+
+    /// A new run with the replay's seed and run number. Starting currency comes from the
+    /// player's profile and modifiers from the seed code, so those are taken as recorded.
+    fn expected_initial(&self, rules: &RulesConfig) -> RunState {
+        let initial = &self.initial;
+        let mut state = RunState::new_run(rules, initial.mode, initial.game_id.run_number, initial.seed);
+        state.moonrocks = initial.moonrocks;
+        state.cheddah = initial.cheddah;
+        state.modifiers = initial.modifiers.clone();
+        state
+    }
+
+    /// The state after the first `step` actions, without checking digests. Used to
+    /// jump around a replay, which can't be stepped backwards directly.
+    pub fn state_at(&self, step: usize, rules: &RulesConfig) -> RunState {
+        let mut state = self.initial.clone();
+        for &action in self.actions.iter().take(step) {
+            state.apply(action, rules);
+        }
+        state
+    }
+
+    /// Plays every action from the initial state and returns the final state,
+    /// stopping at the first step that doesn't match.
+    pub fn play(&self, rules: &RulesConfig) -> Result<RunState, ReplayMismatch> {
+        let mut state = self.initial.clone();
+        for (step, &action) in self.actions.iter().enumerate() {
+            state.apply(action, rules);
+            self.check_step(step, &state)?;
+        }
+        Ok(state)
+    }
+
+    /// Re-simulates the run under `rules` and checks it reaches the result it claims.
+    /// Nothing the replay says about its own setup is trusted: it must name `rules` and
+    /// start where a new run with its seed would.
+    pub fn verify(&self, rules: &RulesConfig) -> Result<ReplayResult, VerifyError> {
+        let expected = rules.fingerprint();
+        if self.rules_hash != expected {
+            return Err(VerifyError::WrongRules { expected, found: self.rules_hash });
+        }
+        if self.initial != self.expected_initial(rules) {
+            return Err(VerifyError::WrongInitialState);
+        }
+
+        let claimed = self.result.ok_or(VerifyError::NoResult)?;
+        let actual = ReplayResult::of(&self.play(rules).map_err(VerifyError::Diverged)?);
+        if actual == claimed { Ok(actual) } else { Err(VerifyError::WrongResult { claimed, actual }) }
+    }
+}
+
+fn serialize_actions<S: Serializer>(actions: &[ReplayAction], serializer: S) -> Result<S::Ok, S::Error> {
+    let codes: String = actions.iter().map(|action| action.code()).collect();
+    serializer.serialize_str(&codes)
+}
+
+fn deserialize_actions<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<ReplayAction>, D::Error> {
+    struct ActionsVisitor;
+
+    impl Visitor<'_> for ActionsVisitor {
+        type Value = Vec<ReplayAction>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a string of action codes")
+        }
+
+        fn visit_str<E: de::Error>(self, codes: &str) -> Result<Self::Value, E> {
+            codes
+                .chars()
+                .map(|code| {
+                    ReplayAction::from_code(code)
+                        .ok_or_else(|| E::invalid_value(de::Unexpected::Char(code), &"an action code"))
+                })
+                .collect()
+        }
+    }
+
+    deserializer.deserialize_str(ActionsVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode::GameMode;
+
+    #[test]
+    fn digest_is_pinned_for_a_fixed_state() {
+        let rules = RulesConfig::default();
+        let mut state = RunState::new_run(&rules, GameMode::Classic, 1, 42);
+        let before = state.digest();
+        state.apply(ReplayAction::Pull, &rules);
+        assert_eq!([before, state.digest()], [0x189B_6C74, 0xFDBA_F495]);
+    }
+
+    #[test]
+    fn digest_ignores_fields_outside_the_projection() {
+        let rules = RulesConfig::default();
+        let mut state = RunState::new_run(&rules, GameMode::Classic, 1, 42);
+        let digest = state.digest();
+        state.level_pulls.push(Orb::Bomb);
+        state.modifiers.clear();
+        assert_eq!(state.digest(), digest);
+        state.health -= 1;
+        assert_ne!(state.digest(), digest);
+    }
+
+    /// A finished run pulling until it ends.
+    fn recorded_run(rules: &RulesConfig, seed: u64) -> Replay {
+        let mut state = RunState::new_run(rules, GameMode::Classic, 1, seed);
+        state.moonrocks = 12;
+        let mut replay = Replay::new(rules, &state);
+        while state.can_pull() {
+            state.apply(ReplayAction::Pull, rules);
+            replay.record(ReplayAction::Pull, &state);
+        }
+        replay.finish(&state);
+        replay
+    }
+
+    #[test]
+    fn honest_replays_verify() {
+        let rules = RulesConfig::default();
+        let replay = recorded_run(&rules, 42);
+        assert_eq!(replay.verify(&rules), Ok(replay.result.unwrap()));
+    }
+
+    #[test]
+    fn replays_under_other_rules_are_rejected() {
+        let rules = RulesConfig::default();
+        let easier = RulesConfig { bomb_damage: 1, ..rules.clone() };
+        let replay = recorded_run(&easier, 42);
+        assert!(matches!(replay.verify(&rules), Err(VerifyError::WrongRules { .. })));
+    }
+
+    #[test]
+    fn tampered_initial_states_are_rejected() {
+        let rules = RulesConfig::default();
+        let mut replay = recorded_run(&rules, 42);
+        replay.initial.health += 10;
+        assert_eq!(replay.verify(&rules), Err(VerifyError::WrongInitialState));
+
+        let mut replay = recorded_run(&rules, 42);
+        replay.initial.orbs.retain(|&orb| orb != Orb::Bomb);
+        assert_eq!(replay.verify(&rules), Err(VerifyError::WrongInitialState));
+    }
+
+    #[test]
+    fn edited_actions_and_results_are_caught() {
+        let rules = RulesConfig::default();
+        let mut replay = recorded_run(&rules, 42);
+        replay.actions[0] = ReplayAction::Timeout;
+        assert!(matches!(replay.verify(&rules), Err(VerifyError::Diverged(ReplayMismatch { step: 0, .. }))));
+
+        let mut replay = recorded_run(&rules, 42);
+        replay.result.as_mut().unwrap().points += 5;
+        assert!(matches!(replay.verify(&rules), Err(VerifyError::WrongResult { .. })));
+    }
+
+    #[test]
+    fn replay_files_parse_unless_newer() {
+        let rules = RulesConfig::default();
+        let replay = recorded_run(&rules, 42);
+
+        let Value::Object(mut data) = serde_json::to_value(&replay).unwrap() else { unreachable!() };
+        data.insert("version".to_string(), Replay::FORMAT_VERSION.into());
+        assert_eq!(Replay::from_json(&serde_json::to_vec(&data).unwrap()), Ok(replay));
+
+        data.insert("version".to_string(), (Replay::FORMAT_VERSION + 1).into());
+        assert!(Replay::from_json(&serde_json::to_vec(&data).unwrap()).is_err());
+    }
+}
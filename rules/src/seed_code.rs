@@ -0,0 +1,171 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::boss::BossModifier;
+use super::state::RunState;
+
+/// Crockford base32: no I, L, O or U, so codes survive being read aloud or copied by hand.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Low bits of the code that hold which run-wide modifiers are on, one per `BossModifier::ALL` entry.
+const MODIFIER_BITS: u32 = 3;
+const _: () = assert!(BossModifier::ALL.len() <= MODIFIER_BITS as usize);
+
+/// Largest value a code can hold: a full 64-bit seed plus the modifier bits.
+const MAX_VALUE: u128 = (1 << (64 + MODIFIER_BITS)) - 1;
+
+/// Seeds drawn for new runs are kept to 40 bits so their codes stay nine characters long.
+pub const SHORT_SEED_BITS: u32 = 40;
+
+/// Everything that decides a run's bag order and run-wide modifiers, written as a short
+/// code like `3F9-Q2M-7XK` that players can share. Daily seeds use all 64 bits, so their
+/// codes are longer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedCode {
+    pub seed: u64,
+    pub modifiers: Vec<BossModifier>,
+}
+
+impl SeedCode {
+    pub fn of(state: &RunState) -> Self {
+        Self { seed: state.seed, modifiers: state.modifiers.clone() }
+    }
+
+    /// A fresh random seed with no modifiers.
+    pub fn random() -> Self {
+        Self { seed: rand::random::<u64>() >> (64 - SHORT_SEED_BITS), modifiers: Vec::new() }
+    }
+
+    fn value(&self) -> u128 {
+        let mask = BossModifier::ALL
+            .iter()
+            .enumerate()
+            .filter(|(_, modifier)| self.modifiers.contains(modifier))
+            .fold(0, |mask, (bit, _)| mask | 1 << bit);
+        (self.seed as u128) << MODIFIER_BITS | mask
+    }
+
+    fn from_value(value: u128) -> Self {
+        let modifiers = BossModifier::ALL
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| value & 1 << bit != 0)
+            .map(|(_, &modifier)| modifier)
+            .collect();
+        Self { seed: (value >> MODIFIER_BITS) as u64, modifiers }
+    }
+}
+
+impl fmt::Display for SeedCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut value = self.value();
+        let mut digits = Vec::new();
+        loop {
+            digits.push(ALPHABET[(value % 32) as usize] as char);
+            value /= 32;
+            if value == 0 {
+                break;
+            }
+        }
+
+        // Grouped in threes from the right, e.g. `3F9-Q2M-7XK`.
+        let code: Vec<String> = digits
+            .chunks(3)
+            .rev()
+            .map(|group| group.iter().rev().collect())
+            .collect();
+        f.write_str(&code.join("-"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeedCodeError {
+    Empty,
+    InvalidCharacter(char),
+    TooLong,
+}
+
+impl fmt::Display for SeedCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedCodeError::Empty => write!(f, "the code is empty"),
+            SeedCodeError::InvalidCharacter(c) => write!(f, "{:?} can't appear in a seed code", c),
+            SeedCodeError::TooLong => write!(f, "the code is too long"),
+        }
+    }
+}
+
+impl std::error::Error for SeedCodeError {}
+
+/// Accepts lower case, ignores dashes and spaces, and reads the letters people commonly
+/// mistype for digits (`O` as 0, `I` and `L` as 1).
+impl FromStr for SeedCode {
+    type Err = SeedCodeError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let mut value: u128 = 0;
+        let mut digits = 0;
+
+        for typed in code.chars().filter(|c| *c != '-' && !c.is_whitespace()) {
+            let c = match typed.to_ascii_uppercase() {
+                'O' => '0',
+                'I' | 'L' => '1',
+                c => c,
+            };
+            let digit = ALPHABET
+                .iter()
+                .position(|&letter| letter as char == c)
+                .ok_or(SeedCodeError::InvalidCharacter(typed))?;
+
+            value = value * 32 + digit as u128;
+            if value > MAX_VALUE {
+                return Err(SeedCodeError::TooLong);
+            }
+            digits += 1;
+        }
+
+        if digits == 0 {
+            return Err(SeedCodeError::Empty);
+        }
+        Ok(Self::from_value(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        let codes = [
+            SeedCode { seed: 0, modifiers: Vec::new() },
+            SeedCode { seed: 0x9F_3A1C_2B7E, modifiers: vec![BossModifier::NoHealing] },
+            SeedCode { seed: u64::MAX, modifiers: BossModifier::ALL.to_vec() },
+        ];
+        for code in codes {
+            assert_eq!(code.to_string().parse::<SeedCode>(), Ok(code));
+        }
+    }
+
+    #[test]
+    fn short_seeds_make_nine_character_codes() {
+        let code = SeedCode { seed: (1 << SHORT_SEED_BITS) - 1, modifiers: Vec::new() };
+        assert_eq!(code.to_string().replace('-', "").len(), 9);
+    }
+
+    #[test]
+    fn typed_codes_are_read_leniently() {
+        let code = SeedCode { seed: 0x1234_5678, modifiers: vec![BossModifier::HiddenMilestone] };
+        let typed = code.to_string().to_lowercase().replace('-', " ").replace('0', "o").replace('1', "l");
+        assert_eq!(typed.parse::<SeedCode>(), Ok(code));
+    }
+
+    #[test]
+    fn bad_codes_are_rejected() {
+        assert_eq!("".parse::<SeedCode>(), Err(SeedCodeError::Empty));
+        assert_eq!(" - ".parse::<SeedCode>(), Err(SeedCodeError::Empty));
+        assert_eq!("3F9-U2M".parse::<SeedCode>(), Err(SeedCodeError::InvalidCharacter('U')));
+        assert_eq!("3F9!".parse::<SeedCode>(), Err(SeedCodeError::InvalidCharacter('!')));
+        assert_eq!("Z".repeat(15).parse::<SeedCode>(), Err(SeedCodeError::TooLong));
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::config::RulesConfig;
+use super::mode::GameMode;
+use super::outcome::{LossReason, RunOutcome};
+use super::replay::{ReplayAction, StepReport};
+use super::state::RunState;
+
+/// How a simulated player decides when to walk away. Pulling is the only action the
+/// rules offer, so strategies differ in when they stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Pulls until the run ends.
+    #[default]
+    AlwaysPull,
+    /// Stops with the points banked as soon as a level's milestone is reached, rather
+    /// than risking them on the next level.
+    StopAtMilestone,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 2] = [Strategy::AlwaysPull, Strategy::StopAtMilestone];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::AlwaysPull => "pull",
+            Strategy::StopAtMilestone => "milestone",
+        }
+    }
+
+    fn stops_after(&self, step: &StepReport) -> bool {
+        match self {
+            Strategy::AlwaysPull => false,
+            Strategy::StopAtMilestone => step.level.as_ref().is_some_and(|level| level.advanced),
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Strategy::ALL
+            .into_iter()
+            .find(|strategy| strategy.name() == name)
+            .ok_or_else(|| format!("unknown strategy {:?}; use pull or milestone", name))
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How one simulated run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimRun {
+    pub ending: Ending,
+    pub score: u32,
+    pub level: u32,
+    pub pulls: usize,
+}
+
+/// Plays one run without any timers: blitz clocks never run out. Runs still going after
+/// `max_pulls`, e.g. endless with bombs that do no damage, are abandoned.
+pub fn simulate_run(rules: &RulesConfig, mode: GameMode, strategy: Strategy, max_pulls: usize, run_number: u64, seed: u64) -> SimRun {
+    let mut state = RunState::new_run(rules, mode, run_number, seed);
+
+    let ending = loop {
+        if let Some(outcome) = state.outcome() {
+            break Ending::of(outcome);
+        }
+        if state.pulls.len() >= max_pulls {
+            break Ending::Abandoned;
+        }
+        let step = state.apply(ReplayAction::Pull, rules);
+        if strategy.stops_after(&step) {
+            break Ending::Stopped;
+        }
+    };
+    // Clearing the level already moved the run on to one the strategy never plays.
+    let level = if ending == Ending::Stopped { state.level - 1 } else { state.level };
+
+    SimRun { ending, score: state.total_score(), level, pulls: state.pulls.len() }
+}
+
+/// How runs ended, in report order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Ending {
+    Won,
+    HealthDepleted,
+    BagEmpty,
+    Timeout,
+    /// The strategy walked away after clearing a level.
+    Stopped,
+    /// Still going after the pull limit.
+    Abandoned,
+}
+
+impl Ending {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ending::Won => "Won",
+            Ending::HealthDepleted => "Health depleted",
+            Ending::BagEmpty => "Bag empty",
+            Ending::Timeout => "Timeout",
+            Ending::Stopped => "Stopped",
+            Ending::Abandoned => "Abandoned",
+        }
+    }
+
+    fn of(outcome: RunOutcome) -> Self {
+        match outcome {
+            RunOutcome::Won => Ending::Won,
+            RunOutcome::Lost(LossReason::HealthDepleted) => Ending::HealthDepleted,
+            RunOutcome::Lost(LossReason::BagEmpty) => Ending::BagEmpty,
+            RunOutcome::Lost(LossReason::Timeout) => Ending::Timeout,
+        }
+    }
+}
+
+/// Totals over many simulated runs. Reports from separate threads are combined with `merge`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimReport {
+    pub games: u64,
+    pub total_score: u64,
+    pub total_pulls: u64,
+    /// Games by the level they ended on.
+    pub levels: BTreeMap<u32, u64>,
+    pub endings: BTreeMap<Ending, u64>,
+}
+
+impl SimReport {
+    pub fn add(&mut self, run: SimRun) {
+        self.games += 1;
+        self.total_score += run.score as u64;
+        self.total_pulls += run.pulls as u64;
+        *self.levels.entry(run.level).or_default() += 1;
+        *self.endings.entry(run.ending).or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: SimReport) {
+        self.games += other.games;
+        self.total_score += other.total_score;
+        self.total_pulls += other.total_pulls;
+        for (level, count) in other.levels {
+            *self.levels.entry(level).or_default() += count;
+        }
+        for (ending, count) in other.endings {
+            *self.endings.entry(ending).or_default() += count;
+        }
+    }
+
+    pub fn wins(&self) -> u64 {
+        self.endings.get(&Ending::Won).copied().unwrap_or_default()
+    }
+
+    /// Fraction of `games`, 0 when nothing was played.
+    pub fn share(&self, count: u64) -> f64 {
+        if self.games == 0 { 0.0 } else { count as f64 / self.games as f64 }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.share(self.wins())
+    }
+
+    pub fn average_score(&self) -> f64 {
+        self.share(self.total_score)
+    }
+
+    pub fn average_pulls(&self) -> f64 {
+        self.share(self.total_pulls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_that_never_end_are_abandoned() {
+        let rules = RulesConfig { bomb_damage: 0, ..RulesConfig::default() };
+        let run = simulate_run(&rules, GameMode::Endless, Strategy::AlwaysPull, 500, 1, 7);
+        assert_eq!((run.ending, run.pulls), (Ending::Abandoned, 500));
+    }
+
+    #[test]
+    fn stopping_at_the_milestone_keeps_the_cleared_level() {
+        let rules = RulesConfig { bomb_damage: 0, ..RulesConfig::default() };
+        let run = simulate_run(&rules, GameMode::Endless, Strategy::StopAtMilestone, 500, 1, 7);
+        assert_eq!((run.ending, run.level), (Ending::Stopped, 1));
+        assert!(run.score >= rules.milestone);
+    }
+}
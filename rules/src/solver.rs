@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use super::config::RulesConfig;
+use super::orb::Orb;
+use super::state::RunState;
+
+/// Stopping walks away from the level as it stands, which only counts as a clear once
+/// the milestone is reached. Pulling is the only way to get there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Pull,
+    Stop,
+}
+
+/// Exact chances of clearing the current level for each choice available from a state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Advice {
+    pub pull_clear_chance: f64,
+    pub stop_clear_chance: f64,
+}
+
+impl Advice {
+    /// Stop when pulling can't do any better, including when the level can no longer be cleared.
+    pub fn best_action(&self) -> Action {
+        if self.pull_clear_chance > self.stop_clear_chance { Action::Pull } else { Action::Stop }
+    }
+
+    pub fn clear_chance(&self) -> f64 {
+        self.pull_clear_chance.max(self.stop_clear_chance)
+    }
+}
+
+/// What stays fixed for the whole level. The memo only holds states of one level.
+/// Bomb damage and healing are included because run-wide modifiers change them
+/// without changing the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Level {
+    max_health: u32,
+    milestone: u32,
+    point_value: u32,
+    bomb_damage: u32,
+    heal_amount: u32,
+}
+
+impl Level {
+    fn of(state: &RunState, rules: &RulesConfig) -> Self {
+        Self {
+            max_health: state.max_health,
+            milestone: state.milestone,
+            point_value: rules.point_value,
+            bomb_damage: state.bomb_damage(rules),
+            heal_amount: state.heal_amount(rules),
+        }
+    }
+}
+
+/// The bag, health and points: everything that changes as a level is played. Points
+/// past the milestone are clamped since they can't change the result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SolverKey {
+    health_orbs: u32,
+    point_orbs: u32,
+    bomb_orbs: u32,
+    health: u32,
+    points: u32,
+}
+
+impl SolverKey {
+    fn of(state: &RunState) -> Self {
+        Self {
+            health_orbs: state.health_orb_count() as u32,
+            point_orbs: state.point_orb_count() as u32,
+            bomb_orbs: state.bomb_orb_count() as u32,
+            health: state.health,
+            points: state.points.min(state.milestone),
+        }
+    }
+
+    fn count(&self, orb: Orb) -> u32 {
+        match orb {
+            Orb::Health => self.health_orbs,
+            Orb::Point => self.point_orbs,
+            Orb::Bomb => self.bomb_orbs,
+        }
+    }
+
+    /// The same effects as [`RunState::apply_orb`] after drawing `orb`.
+    fn after(mut self, orb: Orb, level: &Level) -> Self {
+        match orb {
+            Orb::Health => {
+                self.health_orbs -= 1;
+                if self.health < level.max_health {
+                    self.health = (self.health + level.heal_amount).min(level.max_health);
+                }
+            }
+            Orb::Point => {
+                self.point_orbs -= 1;
+                self.points = (self.points + level.point_value).min(level.milestone);
+            }
+            Orb::Bomb => {
+                self.bomb_orbs -= 1;
+                self.health = self.health.saturating_sub(level.bomb_damage);
+            }
+        }
+        self
+    }
+}
+
+/// Dynamic programming over bag composition, health and points. The state space of a
+/// level is small enough that every reachable state is solved exactly and memoised, so
+/// one solver can be kept for a run and asked about each of its states. Moving to a
+/// level with a different milestone or modifiers starts a fresh memo.
+pub struct Solver {
+    rules: RulesConfig,
+    level: Option<Level>,
+    memo: HashMap<SolverKey, f64>,
+}
+
+impl Solver {
+    pub fn new(rules: &RulesConfig) -> Self {
+        Self { rules: rules.clone(), level: None, memo: HashMap::new() }
+    }
+
+    pub fn rules(&self) -> &RulesConfig {
+        &self.rules
+    }
+
+    pub fn advise(&mut self, state: &RunState) -> Advice {
+        let stop_clear_chance = if state.is_level_complete() { 1.0 } else { 0.0 };
+        if !state.can_pull() {
+            return Advice { pull_clear_chance: stop_clear_chance, stop_clear_chance };
+        }
+
+        let level = Level::of(state, &self.rules);
+        if self.level != Some(level) {
+            self.memo.clear();
+            self.level = Some(level);
+        }
+        Advice { pull_clear_chance: self.solve(SolverKey::of(state), &level), stop_clear_chance }
+    }
+
+    /// Chance of clearing the current level by pulling until it's decided.
+    pub fn clear_chance(&mut self, state: &RunState) -> f64 {
+        self.advise(state).clear_chance()
+    }
+
+    fn solve(&mut self, key: SolverKey, level: &Level) -> f64 {
+        if key.points >= level.milestone {
+            return 1.0;
+        }
+        let total = key.health_orbs + key.point_orbs + key.bomb_orbs;
+        if key.health == 0 || total == 0 {
+            return 0.0;
+        }
+        if let Some(&chance) = self.memo.get(&key) {
+            return chance;
+        }
+
+        let mut chance = 0.0;
+        for orb in Orb::ALL {
+            let count = key.count(orb);
+            if count > 0 {
+                chance += f64::from(count) / f64::from(total) * self.solve(key.after(orb, level), level);
+            }
+        }
+
+        self.memo.insert(key, chance);
+        chance
+    }
+}
+
+/// One-off advice for a state; build a [`Solver`] instead when asking repeatedly.
+pub fn advise(state: &RunState, rules: &RulesConfig) -> Advice {
+    Solver::new(rules).advise(state)
+}
+
+/// Chance of clearing the first level of a fresh run under `rules`, for checking level balance.
+pub fn starting_clear_chance(rules: &RulesConfig) -> f64 {
+    Solver::new(rules).clear_chance(&RunState::new(rules))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BagComposition;
+
+    fn rules(point: u32, bomb: u32) -> RulesConfig {
+        RulesConfig {
+            starting_health: 1,
+            max_health: 1,
+            milestone: 5,
+            starting_bag: BagComposition { health: 0, point, bomb },
+            ..RulesConfig::default()
+        }
+    }
+
+    #[test]
+    fn pulling_is_advised_while_the_level_can_be_cleared() {
+        let rules = rules(1, 1);
+        let advice = advise(&RunState::new(&rules), &rules);
+        assert_eq!(advice, Advice { pull_clear_chance: 0.5, stop_clear_chance: 0.0 });
+        assert_eq!(advice.best_action(), Action::Pull);
+    }
+
+    #[test]
+    fn stopping_is_advised_once_the_level_is_lost() {
+        let rules = RulesConfig { milestone: 10, ..rules(1, 2) };
+        let mut state = RunState::new(&rules);
+        state.remove_orb(Orb::Point);
+        assert_eq!(advise(&state, &rules).best_action(), Action::Stop);
+    }
+
+    #[test]
+    fn a_new_milestone_starts_a_fresh_memo() {
+        let rules = rules(2, 1);
+        let mut solver = Solver::new(&rules);
+        let mut state = RunState::new(&rules);
+        assert_eq!(solver.clear_chance(&state), 2.0 / 3.0);
+
+        // Both point orbs now have to come out before the bomb.
+        state.milestone = 10;
+        assert_eq!(solver.clear_chance(&state), 1.0 / 3.0);
+    }
+}
@@ -0,0 +1,303 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use super::blitz::BlitzClock;
+use super::boss::BossModifier;
+use super::config::RulesConfig;
+use super::game_id::GameId;
+use super::mode::GameMode;
+use super::objectives::{Objective, Reward};
+use super::orb::Orb;
+use super::outcome::{LossReason, RunOutcome};
+
+/// Everything that describes a run in progress. Plain data so it can be driven
+/// by the Bevy plugin, a simulation or a test harness alike.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunState {
+    pub mode: GameMode,
+    pub health: u32,
+    pub max_health: u32,
+    pub points: u32,
+    /// Points banked from levels already completed this run.
+    pub score: u32,
+    pub game_id: GameId,
+    pub seed: u64,
+    /// Orbs drawn so far; each draw gets its own RNG stream from the seed, so the
+    /// run can be replayed or resumed from just these two numbers.
+    pub draws: u64,
+    /// Rule modifiers active on every level, on top of any boss modifier.
+    pub modifiers: Vec<BossModifier>,
+    pub milestone: u32,
+    pub orbs: Vec<Orb>,
+    pub level_pulls: Vec<Orb>,
+    /// Every orb drawn this run, in order.
+    pub pulls: Vec<Orb>,
+    pub level: u32,
+    /// `None` in endless mode.
+    pub final_level: Option<u32>,
+    pub moonrocks: u32,
+    pub cheddah: u32,
+    /// Only present in blitz mode.
+    pub clock: Option<BlitzClock>,
+}
+
+impl Default for RunState {
+    fn default() -> Self {
+        Self::new(&RulesConfig::default())
+    }
+}
+
+impl RunState {
+    pub fn new(rules: &RulesConfig) -> Self {
+        Self::new_run(rules, GameMode::Classic, GameId::default().run_number, 0)
+    }
+
+    pub fn new_run(rules: &RulesConfig, mode: GameMode, run_number: u64, seed: u64) -> Self {
+        Self {
+            mode,
+            health: rules.starting_health,
+            max_health: rules.max_health,
+            points: 0,
+            score: 0,
+            game_id: GameId::new(run_number, seed),
+            seed,
+            draws: 0,
+            modifiers: Vec::new(),
+            milestone: rules.milestone_for_level(mode, 1),
+            orbs: rules.bag_for_level(mode, 1).to_orbs(),
+            level_pulls: Vec::new(),
+            pulls: Vec::new(),
+            level: 1,
+            final_level: rules.final_level(mode),
+            moonrocks: 0,
+            cheddah: 0,
+            clock: (mode == GameMode::Blitz).then(|| BlitzClock::new(&rules.blitz)),
+        }
+    }
+
+    pub fn add_health(&mut self, amount: u32) { 
+        self.health = (self.health + amount).min(self.max_health); 
+    }
+    pub fn add_points(&mut self, amount: u32) { self.points += amount; }
+    pub fn add_moonrocks(&mut self, amount: u32) { self.moonrocks += amount; }
+    pub fn add_cheddah(&mut self, amount: u32) { self.cheddah += amount; }
+    pub fn level_up(&mut self) { self.level += 1; }
+
+    pub fn subtract_health(&mut self, amount: u32) { 
+        self.health = self.health.saturating_sub(amount); 
+    }
+    pub fn remove_orb(&mut self, orb_type: Orb) -> bool {
+        if let Some(pos) = self.orbs.iter().position(|&orb| orb == orb_type) {
+            self.orbs.remove(pos);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_dead(&self) -> bool { self.health == 0 }
+    pub fn is_at_max_health(&self) -> bool { self.health >= self.max_health }
+    pub fn orb_count(&self, orb_type: Orb) -> usize {
+        self.orbs.iter().filter(|&&orb| orb == orb_type).count()
+    }
+    pub fn total_orb_count(&self) -> usize {
+        self.orbs.len()
+    }
+    pub fn health_orb_count(&self) -> usize {
+        self.orb_count(Orb::Health)
+    }
+    pub fn point_orb_count(&self) -> usize {
+        self.orb_count(Orb::Point)
+    }
+    pub fn bomb_orb_count(&self) -> usize {
+        self.orb_count(Orb::Bomb)
+    }
+    pub fn level_pull_count(&self) -> usize {
+        self.level_pulls.len()
+    }
+    pub fn level_pulls_of(&self, orb_type: Orb) -> usize {
+        self.level_pulls.iter().filter(|&&orb| orb == orb_type).count()
+    }
+
+    /// Draws deterministically from the run's seed: the same seed and rules always
+    /// produce the same bag order.
+    pub fn pull_orb(&mut self, rules: &RulesConfig) -> Option<Orb> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(self.draws);
+        self.pull_orb_with(rules, &mut rng)
+    }
+
+    pub fn pull_orb_with<R: Rng + ?Sized>(&mut self, rules: &RulesConfig, rng: &mut R) -> Option<Orb> {
+        if self.orbs.is_empty() {
+            return None;
+        }
+
+        // Sampled as a u64 so wasm32 and 64-bit builds draw the same orb from the same seed.
+        let random_index = rng.gen_range(0..self.orbs.len() as u64) as usize;
+
+        let orb = self.orbs.remove(random_index);
+        self.draws += 1;
+        self.level_pulls.push(orb);
+        self.pulls.push(orb);
+        self.reset_pull_timer(rules);
+        self.apply_orb(orb, rules);
+        Some(orb)
+    }
+
+    /// Endless has no last level, so running dry refills the bag instead of ending the run.
+    /// Returns whether it did.
+    pub fn refill_bag(&mut self, rules: &RulesConfig) -> bool {
+        if self.mode != GameMode::Endless || !self.orbs.is_empty() {
+            return false;
+        }
+        self.orbs = rules.bag_for_level(self.mode, self.level).to_orbs();
+        true
+    }
+
+    /// Applies an orb's effect without touching the bag. Boss modifiers hook in here.
+    pub fn apply_orb(&mut self, orb: Orb, rules: &RulesConfig) {
+        match orb {
+            Orb::Health => {
+                if !self.is_at_max_health() {
+                    self.add_health(self.heal_amount(rules));
+                }
+            },
+            Orb::Point => self.add_points(rules.point_value),
+            Orb::Bomb => self.subtract_health(self.bomb_damage(rules)),
+        }
+    }
+
+    /// Whether a modifier applies right now, either for the whole run or from this level's boss.
+    pub fn has_modifier(&self, modifier: BossModifier, rules: &RulesConfig) -> bool {
+        self.modifiers.contains(&modifier) || rules.has_modifier(self.level, modifier)
+    }
+
+    pub fn bomb_damage(&self, rules: &RulesConfig) -> u32 {
+        if self.has_modifier(BossModifier::DoubleBombDamage, rules) {
+            rules.bomb_damage * 2
+        } else {
+            rules.bomb_damage
+        }
+    }
+
+    pub fn heal_amount(&self, rules: &RulesConfig) -> u32 {
+        if self.has_modifier(BossModifier::NoHealing, rules) { 0 } else { rules.heal_amount }
+    }
+
+    pub fn is_level_complete(&self) -> bool { self.points >= self.milestone }
+    pub fn is_final_level(&self) -> bool { self.final_level.is_some_and(|last| self.level >= last) }
+    pub fn is_boss_level(&self, rules: &RulesConfig) -> bool { rules.boss_for_level(self.level).is_some() }
+
+    /// Points banked so far plus the points of the level in progress.
+    pub fn total_score(&self) -> u32 { self.score + self.points }
+
+    /// Whether another pull is allowed: the level is still open and the run isn't over.
+    pub fn can_pull(&self) -> bool {
+        !self.is_level_complete() && self.outcome().is_none()
+    }
+
+    pub fn grant(&mut self, reward: Reward) {
+        match reward {
+            Reward::Cheddah(amount) => self.add_cheddah(amount),
+            Reward::Moonrocks(amount) => self.add_moonrocks(amount),
+        }
+    }
+
+    /// Pays the boss reward if the completed level was a boss level.
+    pub fn claim_boss_reward(&mut self, rules: &RulesConfig) -> Option<Reward> {
+        if !self.is_level_complete() {
+            return None;
+        }
+
+        let boss = rules.boss_for_level(self.level)?;
+        self.grant(boss.reward);
+        Some(boss.reward)
+    }
+
+    /// Banks the level's points and sets up the next level's milestone and bag.
+    pub fn advance_level(&mut self, rules: &RulesConfig) {
+        self.score += self.points;
+        self.points = 0;
+        self.level_up();
+        self.milestone = rules.milestone_for_level(self.mode, self.level);
+        self.orbs = rules.bag_for_level(self.mode, self.level).to_orbs();
+        self.level_pulls.clear();
+        self.reset_clock(rules);
+    }
+
+    /// Pays out every objective achieved this level and returns them.
+    pub fn claim_objectives(&mut self, rules: &RulesConfig) -> Vec<Objective> {
+        let achieved: Vec<Objective> = rules
+            .objectives_for_level(self.level)
+            .filter(|objective| objective.is_achieved(self))
+            .copied()
+            .collect();
+
+        for objective in &achieved {
+            self.grant(objective.reward);
+        }
+
+        achieved
+    }
+
+    /// Win/loss evaluation: `None` while the run can still continue, including
+    /// when a level is complete and the next one is waiting.
+    pub fn outcome(&self) -> Option<RunOutcome> {
+        if self.is_level_complete() {
+            self.is_final_level().then_some(RunOutcome::Won)
+        } else if self.is_dead() {
+            Some(RunOutcome::Lost(LossReason::HealthDepleted))
+        } else if self.clock.is_some_and(|clock| clock.is_expired()) {
+            Some(RunOutcome::Lost(LossReason::Timeout))
+        } else if self.orbs.is_empty() && self.mode != GameMode::Endless {
+            Some(RunOutcome::Lost(LossReason::BagEmpty))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BagComposition;
+    use crate::replay::ReplayAction;
+
+    /// The draw order is part of the replay and daily formats, so it must never change
+    /// between targets or releases.
+    #[test]
+    fn draws_are_pinned_for_a_fixed_seed() {
+        let rules = RulesConfig::default();
+        let mut state = RunState::new_run(&rules, GameMode::Classic, 1, 42);
+        let draws: Vec<Orb> = (0..12).map_while(|_| state.pull_orb(&rules)).collect();
+        use Orb::{Bomb, Health, Point};
+        assert_eq!(draws, [Point, Bomb, Bomb, Bomb, Bomb, Point, Health, Point, Health, Health, Health, Bomb]);
+    }
+
+    #[test]
+    fn endless_bag_keeps_up_with_the_milestone() {
+        let rules = RulesConfig::default();
+        for level in 1..=30 {
+            let bag = rules.bag_for_level(GameMode::Endless, level);
+            let milestone = rules.milestone_for_level(GameMode::Endless, level);
+            assert!(bag.point * rules.point_value >= milestone, "level {} can't reach {}", level, milestone);
+        }
+    }
+
+    #[test]
+    fn endless_refills_an_empty_bag() {
+        let rules = RulesConfig {
+            starting_health: 1000,
+            max_health: 1000,
+            starting_bag: BagComposition { health: 0, point: 1, bomb: 2 },
+            ..RulesConfig::default()
+        };
+        let mut state = RunState::new_run(&rules, GameMode::Endless, 1, 7);
+        for _ in 0..20 {
+            state.apply(ReplayAction::Pull, &rules);
+            assert!(!state.orbs.is_empty());
+        }
+        assert_eq!(state.outcome(), None);
+        assert!(state.level > 1);
+    }
+}
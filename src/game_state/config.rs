@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+use crate::rules::RulesConfig;
+
+/// The rules every run is played with, loaded once at startup.
+#[derive(Resource, Debug, Clone, Default, Deref, DerefMut)]
+pub struct GameRules(pub RulesConfig);
+
+/// The rules the run on screen follows: a copy of [`GameRules`] while playing, swapped
+/// for the recorded rules during replay playback. Anything that runs in both reads this.
+#[derive(Resource, Debug, Clone, Default, Deref, DerefMut)]
+pub struct ActiveRules(pub RulesConfig);
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use crate::rules::{GameId, GameMode, Objective, Orb, RunOutcome};
+
+/// Sent by input handlers; the game state systems decide what actually happens.
+/// Replay playback sends the recorded pulls the same way, and the events that follow
+/// are marked with `replay`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PullOrbRequested;
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct OrbPulled {
+    pub orb: Orb,
+    pub remaining: usize,
+    /// Set when replay playback caused it rather than play, so listeners that count or
+    /// save progress can skip it.
+    pub replay: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct HealthChanged {
+    pub old: u32,
+    pub new: u32,
+    /// See [`OrbPulled::replay`].
+    pub replay: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PointsChanged {
+    pub old: u32,
+    pub new: u32,
+    /// See [`OrbPulled::replay`].
+    pub replay: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LevelStarted {
+    pub level: u32,
+    pub milestone: u32,
+    pub boss: bool,
+    /// See [`OrbPulled::replay`].
+    pub replay: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ObjectiveCompleted {
+    pub level: u32,
+    pub objective: Objective,
+    /// See [`OrbPulled::replay`].
+    pub replay: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Currency {
+    Moonrocks,
+    Cheddah,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CurrencyChanged {
+    pub currency: Currency,
+    pub old: u32,
+    pub new: u32,
+    /// See [`OrbPulled::replay`].
+    pub replay: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LevelCompleted {
+    pub level: u32,
+    pub points: u32,
+    /// See [`OrbPulled::replay`].
+    pub replay: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RunEnded {
+    pub game_id: GameId,
+    pub mode: GameMode,
+    pub seed: u64,
+    pub outcome: RunOutcome,
+    pub level: u32,
+    /// Total points across every level of the run.
+    pub score: u32,
+}
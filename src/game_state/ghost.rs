@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use crate::persistence::{load_replay, Profile};
+use crate::rules::{GameId, Replay, ReplayAction, RulesConfig, RunState, SeedCode};
+use super::{rules_for_replay, GameRules, PlayerGameState};
+
+/// Where the personal-best run stood after a given number of pulls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GhostPoint {
+    /// Total points across every level so far.
+    pub score: u32,
+    pub health: u32,
+    pub level: u32,
+}
+
+/// The player's best run on the current seed code, replayed ahead of time so the
+/// playing screen can compare pull for pull.
+#[derive(Resource, Debug, Clone)]
+pub struct Ghost {
+    pub game_id: GameId,
+    /// Final score of the best run.
+    pub score: u32,
+    /// Indexed by pull count; entry 0 is the starting state.
+    pub timeline: Vec<GhostPoint>,
+}
+
+impl Ghost {
+    pub fn from_replay(replay: &Replay, rules: &RulesConfig) -> Self {
+        let mut state = replay.initial.clone();
+        let mut timeline = vec![GhostPoint::of(&state)];
+        for &action in &replay.actions {
+            state.apply(action, rules);
+            if action == ReplayAction::Pull {
+                timeline.push(GhostPoint::of(&state));
+            }
+        }
+
+        Self { game_id: replay.game_id, score: state.total_score(), timeline }
+    }
+
+    /// `None` once the ghost's run had already ended by this many pulls.
+    pub fn at(&self, pulls: usize) -> Option<GhostPoint> {
+        self.timeline.get(pulls).copied()
+    }
+
+    pub fn pulls(&self) -> usize {
+        self.timeline.len() - 1
+    }
+}
+
+impl GhostPoint {
+    fn of(state: &RunState) -> Self {
+        Self { score: state.total_score(), health: state.health, level: state.level }
+    }
+}
+
+/// Runs right after `setup_game`. Random seeds almost never repeat, so in practice this
+/// finds a ghost for daily challenges and typed-in seed codes.
+pub fn load_ghost(
+    mut commands: Commands,
+    player_state: Option<Res<PlayerGameState>>,
+    profile: Res<Profile>,
+    rules: Res<GameRules>,
+) {
+    let Some(state) = player_state else {
+        return;
+    };
+    let Some(best) = profile.personal_best(state.mode, &SeedCode::of(&state)) else {
+        return;
+    };
+
+    match load_replay(best.game_id) {
+        Ok(Some(replay)) => {
+            info!("Racing the personal best for this seed: game {} with {} points", best.game_id, best.score);
+            commands.insert_resource(Ghost::from_replay(&replay, &rules_for_replay(&replay, &rules)));
+        }
+        Ok(None) => warn!("Replay of personal best game {} is missing", best.game_id),
+        Err(err) => warn!("Could not load personal best replay: {}", err),
+    }
+}
@@ -1,19 +1,79 @@
 use bevy::prelude::*;
 use crate::interface::GameState;
+use crate::persistence::{HighScores, Profile, ProfileSlots, RunCounter};
+use crate::rules::RulesConfig;
 
-pub mod orb;
+pub mod config;
+pub mod events;
+pub mod ghost;
 pub mod player;
+pub mod replay;
 pub mod systems;
 
-pub use orb::*;
+pub use config::*;
+pub use events::*;
+pub use ghost::*;
 pub use player::*;
+pub use replay::*;
+pub use crate::rules::{GameId, GameMode, LossReason, Orb, RunOutcome};
 
 pub struct GameStatePlugin;
 
 impl Plugin for GameStatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), systems::setup_game)
-            .add_systems(Update, (systems::update_stats_display, systems::check_win_loss_conditions).run_if(in_state(GameState::Playing)))
-            .add_systems(OnExit(GameState::Playing), systems::cleanup_game);
+        let rules = RulesConfig::from_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+            warn!("Falling back to default rules: {}", err);
+            RulesConfig::default()
+        });
+
+        let profile_slots = ProfileSlots::load();
+
+        app.insert_resource(ActiveRules(rules.clone()))
+            .insert_resource(GameRules(rules))
+            .insert_resource(RunCounter::load())
+            .insert_resource(HighScores::load())
+            .insert_resource(Profile::load(profile_slots.active))
+            .insert_resource(profile_slots)
+            .init_resource::<RunSetup>()
+            .add_event::<PullOrbRequested>()
+            .add_event::<OrbPulled>()
+            .add_event::<HealthChanged>()
+            .add_event::<PointsChanged>()
+            .add_event::<CurrencyChanged>()
+            .add_event::<LevelCompleted>()
+            .add_event::<LevelStarted>()
+            .add_event::<ObjectiveCompleted>()
+            .add_event::<RunEnded>()
+            .add_systems(OnEnter(GameState::Playing), (systems::setup_game, load_ghost).chain())
+            .add_systems(
+                Update,
+                (
+                    systems::tick_blitz_clock,
+                    systems::resolve_pull_requests,
+                    (systems::update_stats_display, systems::check_win_loss_conditions)
+                        .run_if(resource_exists_and_changed::<PlayerGameState>),
+                    systems::autosave_run,
+                    systems::record_last_run,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), systems::cleanup_game)
+            .add_systems(Startup, open_replay_from_args)
+            .add_systems(OnEnter(GameState::Replay), setup_replay)
+            .add_systems(
+                Update,
+                (
+                    drive_replay_playback,
+                    systems::resolve_pull_requests,
+                    check_replay_playback,
+                    systems::update_stats_display.run_if(resource_exists_and_changed::<PlayerGameState>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Replay)),
+            )
+            .add_systems(OnExit(GameState::Replay), cleanup_replay)
+            .add_systems(OnEnter(GameState::GameWon), systems::record_profile_stats)
+            .add_systems(OnEnter(GameState::GameLost), systems::record_profile_stats);
     }
 }
\ No newline at end of file
@@ -1,6 +0,0 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Orb {
-    Health,
-    Point,
-    Bomb,
-}
\ No newline at end of file
@@ -1,150 +1,52 @@
 use bevy::prelude::*;
-use rand::Rng;
-use super::orb::Orb;
+use serde::{Deserialize, Serialize};
+use crate::rules::{GameId, GameMode, Orb, Replay, RulesConfig, RunOutcome, RunState, SeedCode};
 
-#[derive(Resource)]
-pub struct PlayerGameState {
-    pub health: u32,
-    pub points: u32,
-    pub game_id: u32,
-    pub milestone: u32,
-    pub orbs: Vec<Orb>,
-    pub level: u32,
-    pub moonrocks: u32,
-    pub cheddah: u32,
-}
+/// The active run, exposed to Bevy systems as a resource.
+#[derive(Resource, Debug, Clone, Default, Deref, DerefMut, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerGameState(pub RunState);
 
-impl Default for PlayerGameState {
-    fn default() -> Self {
-        let mut orbs = Vec::new();
-        
-        for _ in 0..5 {
-            orbs.push(Orb::Health);
-            orbs.push(Orb::Point);
-            orbs.push(Orb::Bomb);
-        }
-        
-        Self {
-            health: 5,
-            points: 0,
-            game_id: 1,
-            milestone: 15,
-            orbs,
-            level: 1,
-            moonrocks: 0,
-            cheddah: 0,
-        }
-    }
-}
-
-#[allow(dead_code)]
 impl PlayerGameState {
-    pub fn health(&self) -> u32 { self.health }
-    pub fn points(&self) -> u32 { self.points }
-    pub fn game_id(&self) -> u32 { self.game_id }
-    pub fn milestone(&self) -> u32 { self.milestone }
-    pub fn orbs(&self) -> &Vec<Orb> { &self.orbs }
-    pub fn level(&self) -> u32 { self.level }
-    pub fn moonrocks(&self) -> u32 { self.moonrocks }
-    pub fn cheddah(&self) -> u32 { self.cheddah }
-
-    pub fn set_health(&mut self, value: u32) { self.health = value; }
-    pub fn set_points(&mut self, value: u32) { self.points = value; }
-    pub fn set_game_id(&mut self, value: u32) { self.game_id = value; }
-    pub fn set_milestone(&mut self, value: u32) { self.milestone = value; }
-    pub fn set_orbs(&mut self, orbs: Vec<Orb>) { self.orbs = orbs; }
-    pub fn set_level(&mut self, value: u32) { self.level = value; }
-    pub fn set_moonrocks(&mut self, value: u32) { self.moonrocks = value; }
-    pub fn set_cheddah(&mut self, value: u32) { self.cheddah = value; }
-
-    pub fn add_health(&mut self, amount: u32) { 
-        self.health = (self.health + amount).min(5); 
-    }
-    pub fn add_points(&mut self, amount: u32) { self.points += amount; }
-    pub fn add_orb(&mut self, orb: Orb) { self.orbs.push(orb); }
-    pub fn add_moonrocks(&mut self, amount: u32) { self.moonrocks += amount; }
-    pub fn add_cheddah(&mut self, amount: u32) { self.cheddah += amount; }
-    
-    pub fn increase_milestone(&mut self) { self.milestone += 1; }
-    pub fn level_up(&mut self) { self.level += 1; }
-
-    pub fn subtract_health(&mut self, amount: u32) { 
-        self.health = self.health.saturating_sub(amount); 
-    }
-    pub fn subtract_points(&mut self, amount: u32) { 
-        self.points = self.points.saturating_sub(amount); 
-    }
-    pub fn remove_orb(&mut self, orb_type: Orb) -> bool {
-        if let Some(pos) = self.orbs.iter().position(|&orb| orb == orb_type) {
-            self.orbs.remove(pos);
-            true
-        } else {
-            false
-        }
-    }
-    pub fn subtract_moonrocks(&mut self, amount: u32) { 
-        self.moonrocks = self.moonrocks.saturating_sub(amount); 
-    }
-    pub fn subtract_cheddah(&mut self, amount: u32) { 
-        self.cheddah = self.cheddah.saturating_sub(amount); 
+    pub fn new(rules: &RulesConfig) -> Self {
+        Self(RunState::new(rules))
     }
 
-    pub fn is_dead(&self) -> bool { self.health == 0 }
-    pub fn is_at_max_health(&self) -> bool { self.health == 5 }
-    pub fn has_orb(&self, orb_type: Orb) -> bool { 
-        self.orbs.contains(&orb_type) 
+    pub fn new_run(rules: &RulesConfig, mode: GameMode, run_number: u64, seed: u64) -> Self {
+        Self(RunState::new_run(rules, mode, run_number, seed))
     }
-    pub fn orb_count(&self, orb_type: Orb) -> usize {
-        self.orbs.iter().filter(|&&orb| orb == orb_type).count()
-    }
-    pub fn total_orb_count(&self) -> usize {
-        self.orbs.len()
-    }
-    pub fn health_orb_count(&self) -> usize {
-        self.orb_count(Orb::Health)
-    }
-    pub fn point_orb_count(&self) -> usize {
-        self.orb_count(Orb::Point)
-    }
-    pub fn bomb_orb_count(&self) -> usize {
-        self.orb_count(Orb::Bomb)
-    }
-    pub fn has_moonrocks(&self, count: u32) -> bool { self.moonrocks >= count }
-    pub fn has_cheddah(&self, count: u32) -> bool { self.cheddah >= count }
-    
-    pub fn reset_to_defaults(&mut self) {
-        *self = Self::default();
-    }
-
-    pub fn pull_orb(&mut self) -> Option<Orb> {
-        if self.orbs.is_empty() {
-            return None;
-        }
+}
 
-        let mut rng = rand::thread_rng();
-        let random_index = rng.gen_range(0..self.orbs.len());
-        
-        let orb = self.orbs.remove(random_index);
-        
-        match orb {
-            Orb::Health => {
-                if !self.is_at_max_health() {
-                    self.add_health(1);
-                    info!("Consumed Health orb: +1 health (now {})", self.health);
-                } else {
-                    info!("Consumed Health orb: no effect (health already at max)");
-                }
-            },
-            Orb::Point => {
-                self.add_points(5);
-                info!("Consumed Point orb: +5 points");
-            },
-            Orb::Bomb => {
-                self.subtract_health(2);
-                info!("Consumed Bomb orb: -2 health");
-            },
-        }
+/// What the menu asked for; read when the next run starts.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct RunSetup {
+    pub mode: GameMode,
+    /// Pick up the saved run instead of starting a new one.
+    pub resume: bool,
+    /// Seed and modifiers typed in on the menu; a random seed is used otherwise.
+    /// Ignored by the daily challenge, which always plays the day's seed.
+    pub seed: Option<SeedCode>,
+    /// The recording the replay viewer opens next.
+    pub replay: Option<Replay>,
+}
 
-        Some(orb)
-    }
-}
\ No newline at end of file
+/// How the most recent run ended. Outlives `PlayerGameState` so the end screens can show it.
+#[derive(Resource, Debug, Clone)]
+pub struct LastRun {
+    pub game_id: GameId,
+    pub mode: GameMode,
+    pub seed_code: SeedCode,
+    pub outcome: RunOutcome,
+    pub level: u32,
+    pub score: u32,
+    /// Currency balances when the run ended, including what the profile brought in.
+    pub moonrocks: u32,
+    pub cheddah: u32,
+    pub pulls: Vec<Orb>,
+    /// Best score on the same mode and seed code before this run, if there was one.
+    pub personal_best: Option<u32>,
+    /// 0-based rank if the run made the high-score table.
+    pub high_score_rank: Option<usize>,
+    /// A daily challenge replay that didn't count because the day's attempt was already used.
+    pub practice: bool,
+}
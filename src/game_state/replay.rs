@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use crate::interface::GameState;
+use crate::persistence::{load_rules, read_replay_file};
+use crate::rules::{find_flag_value, Replay, ReplayAction, ReplayMismatch, RulesConfig};
+use super::{ActiveRules, GameRules, PlayerGameState, PullOrbRequested, RunSetup};
+
+/// Records the run being played. Absent for runs resumed from a save that predates replays.
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct ReplayRecorder(pub Replay);
+
+/// Seconds between actions at 1x speed.
+pub const REPLAY_STEP_SECONDS: f32 = 0.6;
+
+pub const REPLAY_SPEEDS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// The replay being watched and where the viewer is in it.
+#[derive(Resource, Debug, Clone)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    /// Number of actions applied so far.
+    pub position: usize,
+    pub paused: bool,
+    /// Index into [`REPLAY_SPEEDS`].
+    pub speed: usize,
+    pub mismatch: Option<ReplayMismatch>,
+    elapsed: f32,
+    step_once: bool,
+    /// Set by `drive_replay_playback` and checked once the action has been resolved.
+    awaiting: Option<usize>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            position: 0,
+            paused: false,
+            speed: 2,
+            mismatch: None,
+            elapsed: 0.0,
+            step_once: false,
+            awaiting: None,
+        }
+    }
+
+    /// Whether `drive_replay_playback` has issued an action that hasn't been checked yet.
+    /// Pull requests are only resolved then, so one queued before a seek is dropped.
+    pub fn is_awaiting(&self) -> bool {
+        self.awaiting.is_some()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.len()
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        REPLAY_SPEEDS[self.speed]
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(REPLAY_SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// Pauses and plays exactly one action on the next frame.
+    pub fn step_forward(&mut self) {
+        self.paused = true;
+        self.step_once = true;
+    }
+
+    pub fn step_back(&mut self, state: &mut PlayerGameState, rules: &RulesConfig) {
+        self.paused = true;
+        self.seek(self.position.saturating_sub(1), state, rules);
+    }
+
+    /// Jumps straight to the state after `step` actions. Going backwards can only be
+    /// done by replaying from the start, so this rebuilds the state rather than playing
+    /// through the game systems.
+    pub fn seek(&mut self, step: usize, state: &mut PlayerGameState, rules: &RulesConfig) {
+        let step = step.min(self.replay.len());
+        state.0 = self.replay.state_at(step, rules);
+        self.position = step;
+        self.elapsed = 0.0;
+        self.step_once = false;
+        self.awaiting = None;
+    }
+}
+
+/// Queues the replay named by `--replay <file>` and opens the viewer on startup.
+pub fn open_replay_from_args(mut run_setup: ResMut<RunSetup>, mut next_state: ResMut<NextState<GameState>>) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = match find_flag_value(&args, "--replay") {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(err) => {
+            warn!("Ignoring --replay: {}", err);
+            return;
+        }
+    };
+
+    match read_replay_file(&path) {
+        Ok(replay) => {
+            run_setup.replay = Some(replay);
+            next_state.set(GameState::Replay);
+        }
+        Err(err) => warn!("Could not open replay {}: {}", path, err),
+    }
+}
+
+/// The rules `replay` was recorded under: the current ones if they match, otherwise
+/// the copy saved alongside it. Falls back to the current rules, in which case playback
+/// will most likely report a mismatch.
+pub fn rules_for_replay(replay: &Replay, current: &RulesConfig) -> RulesConfig {
+    if replay.rules_hash == current.fingerprint() {
+        return current.clone();
+    }
+
+    match load_rules(replay.rules_hash) {
+        Ok(Some(rules)) => rules,
+        Ok(None) => {
+            warn!("Rules {:08X} of game {} aren't saved; using the current rules", replay.rules_hash, replay.game_id);
+            current.clone()
+        }
+        Err(err) => {
+            warn!("Could not load the rules of game {}, using the current rules: {}", replay.game_id, err);
+            current.clone()
+        }
+    }
+}
+
+pub fn setup_replay(
+    mut commands: Commands,
+    rules: Res<GameRules>,
+    mut active_rules: ResMut<ActiveRules>,
+    mut run_setup: ResMut<RunSetup>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(replay) = run_setup.replay.take() else {
+        warn!("No replay to play back");
+        next_state.set(GameState::Menu);
+        return;
+    };
+
+    info!("Playing back game {} ({} actions)", replay.game_id, replay.len());
+    commands.insert_resource(PlayerGameState(replay.initial.clone()));
+    active_rules.0 = rules_for_replay(&replay, &rules);
+    commands.insert_resource(ReplayPlayback::new(replay));
+}
+
+/// Issues the next recorded action when it's due. Pulls go through `PullOrbRequested`
+/// exactly like a click; a timeout has no request event, so it's applied here.
+pub fn drive_replay_playback(
+    time: Res<Time>,
+    playback: Option<ResMut<ReplayPlayback>>,
+    player_state: Option<ResMut<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    mut pull_requests: EventWriter<PullOrbRequested>,
+) {
+    let (Some(mut playback), Some(mut state)) = (playback, player_state) else {
+        return;
+    };
+    if playback.is_finished() {
+        playback.step_once = false;
+        return;
+    }
+
+    if !std::mem::take(&mut playback.step_once) {
+        if playback.paused {
+            return;
+        }
+        playback.elapsed += time.delta_secs() * playback.speed_multiplier();
+        if playback.elapsed < REPLAY_STEP_SECONDS {
+            return;
+        }
+        playback.elapsed -= REPLAY_STEP_SECONDS;
+    }
+
+    let step = playback.position;
+    match playback.replay.actions[step] {
+        ReplayAction::Pull => {
+            pull_requests.write(PullOrbRequested);
+        }
+        ReplayAction::Timeout => {
+            state.apply(ReplayAction::Timeout, &rules);
+        }
+    }
+    playback.awaiting = Some(step);
+    playback.position += 1;
+}
+
+/// Compares the state after each played action with the recording.
+pub fn check_replay_playback(playback: Option<ResMut<ReplayPlayback>>, player_state: Option<Res<PlayerGameState>>) {
+    let (Some(mut playback), Some(state)) = (playback, player_state) else {
+        return;
+    };
+    let Some(step) = playback.awaiting.take() else {
+        return;
+    };
+
+    if let Err(mismatch) = playback.replay.check_step(step, &state) {
+        warn!("Replay of game {} {}", playback.replay.game_id, mismatch);
+        playback.paused = true;
+        playback.mismatch.get_or_insert(mismatch);
+    }
+}
+
+pub fn cleanup_replay(mut commands: Commands) {
+    commands.remove_resource::<PlayerGameState>();
+    commands.remove_resource::<ReplayPlayback>();
+}
@@ -1,15 +1,80 @@
 use bevy::prelude::*;
+use chrono::Local;
 use crate::interface::{GameState, StatDisplay, StatType};
-use super::PlayerGameState;
+use crate::persistence::{pin_replay, save_replay, HighScoreEntry, HighScores, Profile, RunCounter, RunRecord, SavedRun};
+use crate::rules::{BossModifier, DailyChallenge, GameMode, Orb, Replay, ReplayAction, SeedCode};
+use super::{
+    ActiveRules, Currency, CurrencyChanged, GameRules, HealthChanged, LastRun, LevelCompleted, LevelStarted,
+    LossReason, ObjectiveCompleted, OrbPulled, PlayerGameState, PointsChanged, PullOrbRequested,
+    Ghost, ReplayPlayback, ReplayRecorder, RunEnded, RunOutcome, RunSetup,
+};
 
-pub fn setup_game(mut commands: Commands) {
-    info!("Setting up game state");
-    
-    commands.insert_resource(PlayerGameState::default());
+pub fn setup_game(
+    mut commands: Commands,
+    rules: Res<GameRules>,
+    mut active_rules: ResMut<ActiveRules>,
+    mut run_setup: ResMut<RunSetup>,
+    mut run_counter: ResMut<RunCounter>,
+    mut profile: ResMut<Profile>,
+) {
+    active_rules.0 = rules.0.clone();
+
+    if std::mem::take(&mut run_setup.resume) {
+        match SavedRun::load(profile.slot) {
+            Ok(Some(saved)) => {
+                let run = saved.run;
+                info!("Resuming {} game {} at level {}", run.mode.name(), run.game_id, run.level);
+                match saved.replay {
+                    Some(replay) => commands.insert_resource(ReplayRecorder(replay)),
+                    None => warn!("Saved run predates replays, game {} won't be recorded", run.game_id),
+                }
+                commands.insert_resource(PlayerGameState(run));
+                return;
+            }
+            Ok(None) => warn!("No saved run to resume, starting a new one"),
+            Err(err) => warn!("Could not load saved run, starting a new one: {}", err),
+        }
+    }
+
+    let run_number = run_counter.next_run();
+    if let Err(err) = run_counter.save() {
+        warn!("Could not save run counter: {}", err);
+    }
+
+    let daily = (run_setup.mode == GameMode::Daily).then(DailyChallenge::today);
+    let typed_code = run_setup.seed.take();
+    let code = match daily {
+        Some(daily) => SeedCode { seed: daily.seed, modifiers: vec![daily.modifier] },
+        None => typed_code.unwrap_or_else(SeedCode::random),
+    };
+
+    let mut state = PlayerGameState::new_run(&rules, run_setup.mode, run_number, code.seed);
+    state.modifiers = code.modifiers.clone();
+    info!("Setting up {} game state for game {} with seed code {}", state.mode.name(), state.game_id, code);
+
+    state.moonrocks = profile.moonrocks;
+    state.cheddah = profile.cheddah;
+
+    profile.stats.runs_started += 1;
+
+    if let Some(daily) = daily {
+        if profile.start_daily_attempt(daily.key(), state.game_id) {
+            info!("Scored daily challenge attempt for {}", daily.key());
+        } else {
+            info!("Daily challenge for {} already attempted, this run is practice", daily.key());
+        }
+    }
+    if let Err(err) = profile.save() {
+        warn!("Could not save profile: {}", err);
+    }
+
+    commands.insert_resource(ReplayRecorder(Replay::new(&rules, &state)));
+    commands.insert_resource(state);
 }
 
 pub fn update_stats_display(
     player_state: Option<Res<PlayerGameState>>,
+    rules: Res<ActiveRules>,
     mut stats_query: Query<(&mut Text, &StatDisplay)>,
 ) {
     if let Some(state) = player_state {
@@ -18,13 +83,18 @@ pub fn update_stats_display(
                 StatType::Health => format!("Health: {}", state.health),
                 StatType::Points => format!("Points: {}", state.points),
                 StatType::GameId => format!("Game ID: {}", state.game_id),
+                StatType::SeedCode => format!("Seed code: {}", SeedCode::of(&state)),
+                StatType::Milestone if state.has_modifier(BossModifier::HiddenMilestone, &rules) => "Milestone: ???".to_string(),
                 StatType::Milestone => format!("Milestone: {}", state.milestone),
                 StatType::Orbs => format!("Orbs: H:{} P:{} B:{}", 
                     state.health_orb_count(), 
                     state.point_orb_count(), 
                     state.bomb_orb_count()
                 ),
-                StatType::Level => format!("Level: {}", state.level),
+                StatType::Level => match state.final_level {
+                    Some(final_level) => format!("Level: {}/{}", state.level, final_level),
+                    None => format!("Level: {}", state.level),
+                },
                 StatType::Moonrocks => format!("Moonrocks: {}", state.moonrocks),
                 StatType::Cheddah => format!("Cheddah: {}", state.cheddah),
             };
@@ -32,24 +102,400 @@ pub fn update_stats_display(
     }
 }
 
+pub fn tick_blitz_clock(
+    time: Res<Time>,
+    player_state: Option<ResMut<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    mut pull_requests: EventWriter<PullOrbRequested>,
+) {
+    let Some(mut state) = player_state else {
+        return;
+    };
+    if state.clock.is_none() {
+        return;
+    }
+
+    let was_expired = state.clock.is_some_and(|clock| clock.is_expired());
+    if state.tick(time.delta_secs(), &rules) {
+        info!("Pull timer ran out, pulling automatically");
+        pull_requests.write(PullOrbRequested);
+    }
+
+    // The level clock running out is recorded as an action; the ticks leading up to it aren't.
+    if !was_expired
+        && state.clock.is_some_and(|clock| clock.is_expired())
+        && let Some(mut recorder) = recorder
+    {
+        recorder.record(ReplayAction::Timeout, &state);
+    }
+}
+
+pub fn resolve_pull_requests(
+    mut requests: EventReader<PullOrbRequested>,
+    player_state: Option<ResMut<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut orb_pulled: EventWriter<OrbPulled>,
+    mut health_changed: EventWriter<HealthChanged>,
+    mut points_changed: EventWriter<PointsChanged>,
+    mut currency_changed: EventWriter<CurrencyChanged>,
+    mut level_completed: EventWriter<LevelCompleted>,
+    mut level_started: EventWriter<LevelStarted>,
+    mut objective_completed: EventWriter<ObjectiveCompleted>,
+) {
+    let Some(mut state) = player_state else {
+        requests.clear();
+        return;
+    };
+    if playback.as_ref().is_some_and(|playback| !playback.is_awaiting()) {
+        requests.clear();
+        return;
+    }
+    let replay = playback.is_some();
+    let mut recorder = recorder;
+
+    for _ in requests.read() {
+        // Once the run is decided, extra clicks in the same frame must not keep drawing.
+        if !state.can_pull() {
+            continue;
+        }
+
+        let (health, points, moonrocks, cheddah) = (state.health, state.points, state.moonrocks, state.cheddah);
+
+        let report = state.apply(ReplayAction::Pull, &rules);
+        let Some(orb) = report.orb else {
+            continue;
+        };
+        if let Some(recorder) = &mut recorder {
+            recorder.record(ReplayAction::Pull, &state);
+        }
+
+        log_pull(orb, health, &state, &rules);
+        if report.bag_refilled {
+            info!("Bag refilled with {} orbs", state.total_orb_count());
+        }
+        orb_pulled.write(OrbPulled { orb, remaining: state.total_orb_count(), replay });
+
+        if state.health != health {
+            health_changed.write(HealthChanged { old: health, new: state.health, replay });
+        }
+
+        let Some(level) = report.level else {
+            if state.points != points {
+                points_changed.write(PointsChanged { old: points, new: state.points, replay });
+            }
+            write_currency_changes(&state, moonrocks, cheddah, replay, &mut currency_changed);
+            continue;
+        };
+
+        if level.points != points {
+            points_changed.write(PointsChanged { old: points, new: level.points, replay });
+        }
+        info!("Level {} complete! Points: {}", level.level, level.points);
+        level_completed.write(LevelCompleted { level: level.level, points: level.points, replay });
+        for objective in level.objectives {
+            info!("Objective complete: {}", objective.description());
+            objective_completed.write(ObjectiveCompleted { level: level.level, objective, replay });
+        }
+        if level.boss_reward.is_some()
+            && let Some(boss) = rules.boss_for_level(level.level)
+        {
+            info!("Boss defeated: {}", boss.name);
+        }
+        write_currency_changes(&state, moonrocks, cheddah, replay, &mut currency_changed);
+
+        if level.advanced {
+            match rules.boss_for_level(state.level) {
+                Some(boss) => info!("Level {}: boss level {} ({})", state.level, boss.name, boss.modifier.description()),
+                None => info!("Level {}: milestone {}", state.level, state.milestone),
+            }
+            points_changed.write(PointsChanged { old: level.points, new: state.points, replay });
+            level_started.write(LevelStarted {
+                level: state.level,
+                milestone: state.milestone,
+                boss: state.is_boss_level(&rules),
+                replay,
+            });
+        }
+    }
+}
+
+fn log_pull(orb: Orb, health: u32, state: &PlayerGameState, rules: &ActiveRules) {
+    match orb {
+        Orb::Health if state.health > health => {
+            info!("Consumed Health orb: +{} health (now {})", state.health - health, state.health);
+        }
+        Orb::Health => info!("Consumed Health orb: no effect (health already at max)"),
+        Orb::Point => info!("Consumed Point orb: +{} points", rules.point_value),
+        Orb::Bomb => info!("Consumed Bomb orb: -{} health", state.bomb_damage(rules)),
+    }
+}
+
+fn write_currency_changes(
+    state: &PlayerGameState,
+    moonrocks: u32,
+    cheddah: u32,
+    replay: bool,
+    currency_changed: &mut EventWriter<CurrencyChanged>,
+) {
+    if state.moonrocks != moonrocks {
+        currency_changed.write(CurrencyChanged { currency: Currency::Moonrocks, old: moonrocks, new: state.moonrocks, replay });
+    }
+    if state.cheddah != cheddah {
+        currency_changed.write(CurrencyChanged { currency: Currency::Cheddah, old: cheddah, new: state.cheddah, replay });
+    }
+}
+
 pub fn check_win_loss_conditions(
     player_state: Option<Res<PlayerGameState>>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut run_ended: EventWriter<RunEnded>,
 ) {
-    if let Some(state) = player_state {
-        if state.points >= state.milestone {
-            info!("Player wins! Points: {} >= Milestone: {}", state.points, state.milestone);
-            next_state.set(GameState::GameWon);
-        }
-        else if state.health == 0 {
-            info!("Player loses! Health reached zero.");
-            next_state.set(GameState::GameLost);
+    let Some(state) = player_state else {
+        return;
+    };
+    let Some(outcome) = state.outcome() else {
+        return;
+    };
+
+    match outcome {
+        RunOutcome::Won => info!("Player wins! Final score: {}", state.total_score()),
+        RunOutcome::Lost(LossReason::HealthDepleted) => info!("Player loses! Health reached zero."),
+        RunOutcome::Lost(LossReason::BagEmpty) => info!("Player loses! The bag ran out before the milestone."),
+        RunOutcome::Lost(LossReason::Timeout) => info!("Player loses! Time ran out."),
+    }
+    run_ended.write(RunEnded { game_id: state.game_id, mode: state.mode, seed: state.seed, outcome, level: state.level, score: state.total_score() });
+    next_state.set(match outcome {
+        RunOutcome::Won => GameState::GameWon,
+        RunOutcome::Lost(_) => GameState::GameLost,
+    });
+}
+
+/// Saves after every pull so a run survives quitting or a crash. Finished runs are
+/// left alone; `record_last_run` deletes their save.
+pub fn autosave_run(
+    player_state: Option<Res<PlayerGameState>>,
+    recorder: Option<Res<ReplayRecorder>>,
+    profile: Res<Profile>,
+    mut orb_pulled: EventReader<OrbPulled>,
+) {
+    if orb_pulled.read().filter(|pulled| !pulled.replay).count() == 0 {
+        return;
+    }
+    let (Some(state), Some(recorder)) = (player_state, recorder) else {
+        return;
+    };
+
+    if state.outcome().is_none() {
+        save_run(&profile, &state, &recorder);
+    }
+}
+
+fn save_run(profile: &Profile, state: &PlayerGameState, recorder: &ReplayRecorder) {
+    if let Err(err) = SavedRun::save(profile.slot, state, Some(recorder)) {
+        warn!("Could not save run: {}", err);
+    }
+}
+
+pub fn record_last_run(
+    mut commands: Commands,
+    player_state: Option<Res<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    recorder: Option<Res<ReplayRecorder>>,
+    mut run_ended: EventReader<RunEnded>,
+    mut high_scores: ResMut<HighScores>,
+    mut profile: ResMut<Profile>,
+) {
+    for event in run_ended.read() {
+        let seed_code = player_state.as_ref().map_or(
+            SeedCode { seed: event.seed, modifiers: Vec::new() },
+            |state| SeedCode::of(state),
+        );
+        let personal_best = profile.personal_best(event.mode, &seed_code).map(|best| best.score);
+
+        let (high_score_rank, practice) =
+            record_finished_run(player_state.as_deref(), &rules, recorder.as_deref(), event, &mut high_scores, &mut profile);
+
+        commands.insert_resource(LastRun {
+            game_id: event.game_id,
+            mode: event.mode,
+            seed_code,
+            outcome: event.outcome,
+            level: event.level,
+            score: event.score,
+            moonrocks: player_state.as_ref().map_or(0, |state| state.moonrocks),
+            cheddah: player_state.as_ref().map_or(0, |state| state.cheddah),
+            pulls: player_state.as_ref().map(|state| state.pulls.clone()).unwrap_or_default(),
+            personal_best,
+            high_score_rank,
+            practice,
+        });
+    }
+}
+
+/// Persists a finished run everywhere it belongs. Returns its high-score rank and
+/// whether it was a daily practice run. Only the day's first attempt counts, so practice
+/// runs stay out of the high scores and personal bests.
+fn record_finished_run(
+    state: Option<&PlayerGameState>,
+    rules: &ActiveRules,
+    recorder: Option<&ReplayRecorder>,
+    event: &RunEnded,
+    high_scores: &mut HighScores,
+    profile: &mut Profile,
+) -> (Option<usize>, bool) {
+    let practice = event.mode == GameMode::Daily && !record_daily_result(profile, event);
+
+    if let (Some(recorder), Some(state)) = (recorder, state) {
+        let mut replay = recorder.0.clone();
+        replay.finish(state);
+        match save_replay(&replay, rules) {
+            // Only a run with a replay on disk can become the ghost for its seed.
+            Ok(()) if !practice => record_personal_best(profile, state, event),
+            Ok(()) => {}
+            Err(err) => warn!("Could not save replay: {}", err),
         }
     }
+    if let Err(err) = SavedRun::clear(profile.slot) {
+        warn!("Could not delete saved run: {}", err);
+    }
+    if let Some(state) = state
+        && let Err(err) = RunRecord::new(state, rules, event.outcome).append()
+    {
+        warn!("Could not append to run history: {}", err);
+    }
+
+    let high_score_rank = if practice { None } else { record_high_score(high_scores, rules, event, state) };
+    (high_score_rank, practice)
+}
+
+fn record_personal_best(profile: &mut Profile, state: &PlayerGameState, event: &RunEnded) {
+    let code = SeedCode::of(state);
+    let previous = profile.personal_best(event.mode, &code).map(|best| best.game_id);
+    if !profile.record_personal_best(event.mode, &code, event.game_id, event.score) {
+        return;
+    }
+
+    info!("New personal best for this seed: {}", event.score);
+    if let Err(err) = profile.save() {
+        warn!("Could not save profile: {}", err);
+    }
+    if let Err(err) = pin_replay(event.game_id, previous) {
+        warn!("Could not pin the personal best replay: {}", err);
+    }
+}
+
+fn record_high_score(
+    high_scores: &mut HighScores,
+    rules: &ActiveRules,
+    event: &RunEnded,
+    state: Option<&PlayerGameState>,
+) -> Option<usize> {
+    let rank = high_scores.insert(HighScoreEntry {
+        score: event.score,
+        level: event.level,
+        game_id: event.game_id,
+        mode: event.mode,
+        rules_hash: rules.fingerprint(),
+        seed: event.seed,
+        modifiers: state.map(|state| state.modifiers.clone()).unwrap_or_default(),
+        date: Local::now().format("%Y-%m-%d").to_string(),
+    })?;
+
+    info!("New high score #{}: {}", rank + 1, event.score);
+    if let Err(err) = high_scores.save() {
+        warn!("Could not save high scores: {}", err);
+    }
+    Some(rank)
+}
+
+/// Returns `false` when the run wasn't the day's scored attempt.
+fn record_daily_result(profile: &mut Profile, event: &RunEnded) -> bool {
+    if !profile.finish_daily_attempt(event.game_id, event.score, event.outcome) {
+        return false;
+    }
+
+    info!("Daily challenge scored: {}", event.score);
+    if let Err(err) = profile.save() {
+        warn!("Could not save profile: {}", err);
+    }
+    true
+}
+
+/// Runs on entering `GameWon` / `GameLost`, once `record_last_run` has stored the result.
+pub fn record_profile_stats(last_run: Option<Res<LastRun>>, mut profile: ResMut<Profile>) {
+    let Some(run) = last_run else {
+        return;
+    };
+
+    // The run started from the profile's balances, so anything above them was earned this run.
+    let moonrocks_earned = run.moonrocks.saturating_sub(profile.moonrocks);
+    profile.stats.record_run(run.outcome, run.score, run.level, moonrocks_earned, &run.pulls);
+    profile.moonrocks = run.moonrocks;
+    profile.cheddah = run.cheddah;
+    if let Err(err) = profile.save() {
+        warn!("Could not save profile: {}", err);
+    }
 }
 
-pub fn cleanup_game(mut commands: Commands) {
+pub fn cleanup_game(
+    mut commands: Commands,
+    player_state: Option<Res<PlayerGameState>>,
+    recorder: Option<Res<ReplayRecorder>>,
+    profile: Res<Profile>,
+) {
     info!("Cleaning up game state");
-    
+
+    // Quitting mid-run keeps it for Continue, including time spent since the last pull.
+    if let (Some(state), Some(recorder)) = (player_state, recorder)
+        && state.outcome().is_none()
+    {
+        save_run(&profile, &state, &recorder);
+    }
+
     commands.remove_resource::<PlayerGameState>();
-}
\ No newline at end of file
+    commands.remove_resource::<ReplayRecorder>();
+    commands.remove_resource::<Ghost>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::with_memory_storage;
+    use crate::rules::{GameId, RulesConfig};
+
+    fn finish_daily(profile: &mut Profile, high_scores: &mut HighScores, run_number: u64) -> (Option<usize>, bool) {
+        let rules = ActiveRules(RulesConfig::default());
+        let state = PlayerGameState::new_run(&rules, GameMode::Daily, run_number, 42);
+        let recorder = ReplayRecorder(Replay::new(&rules, &state));
+        let event = RunEnded {
+            game_id: state.game_id,
+            mode: GameMode::Daily,
+            seed: state.seed,
+            outcome: RunOutcome::Lost(LossReason::HealthDepleted),
+            level: 1,
+            score: 10,
+        };
+        profile.start_daily_attempt("2026-01-01".to_string(), event.game_id);
+        record_finished_run(Some(&state), &rules, Some(&recorder), &event, high_scores, profile)
+    }
+
+    #[test]
+    fn daily_practice_runs_are_not_ranked() {
+        with_memory_storage(|| {
+            let mut profile = Profile::default();
+            let mut high_scores = HighScores::default();
+
+            assert_eq!(finish_daily(&mut profile, &mut high_scores, 1), (Some(0), false));
+            let best = profile.personal_best(GameMode::Daily, &SeedCode { seed: 42, modifiers: Vec::new() }).cloned();
+            assert_eq!(best.map(|best| best.game_id), Some(GameId::new(1, 42)));
+
+            assert_eq!(finish_daily(&mut profile, &mut high_scores, 2), (None, true));
+            assert_eq!(high_scores.entries.len(), 1);
+            let best = profile.personal_best(GameMode::Daily, &SeedCode { seed: 42, modifiers: Vec::new() }).cloned();
+            assert_eq!(best.map(|best| best.game_id), Some(GameId::new(1, 42)));
+        });
+    }
+}
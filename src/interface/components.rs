@@ -1,10 +1,62 @@
 use bevy::prelude::*;
+use crate::game_state::GameMode;
 
 #[derive(Component)]
 pub struct MenuUI;
 
 #[derive(Component)]
-pub struct StartButton;
+pub struct StartButton {
+    pub mode: GameMode,
+}
+
+#[derive(Component)]
+pub struct ContinueButton;
+
+/// The menu's seed code field.
+#[derive(Component)]
+pub struct SeedInput;
+
+/// Says what the typed seed code will do, or why it can't be used.
+#[derive(Component)]
+pub struct SeedCodeStatus;
+
+#[derive(Component)]
+pub struct ProfileButton;
+
+#[derive(Component)]
+pub struct ProfileUI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotAction {
+    Select,
+    Rename,
+    Delete,
+}
+
+#[derive(Component)]
+pub struct ProfileSlotButton {
+    pub slot: u32,
+    pub action: SlotAction,
+}
+
+/// The field a profile's new name is typed into.
+#[derive(Component)]
+pub struct ProfileNameInput {
+    pub slot: u32,
+}
+
+#[derive(Component)]
+pub struct NewProfileButton;
+
+#[derive(Component)]
+pub struct LeaderboardButton;
+
+#[derive(Component)]
+pub struct LeaderboardUI;
+
+/// Returns to the main menu from a secondary screen.
+#[derive(Component)]
+pub struct BackButton;
 
 #[derive(Component)]
 pub struct PlayingUI;
@@ -15,8 +67,63 @@ pub struct QuitButton;
 #[derive(Component)]
 pub struct PullOrbButton;
 
+#[derive(Component)]
+pub struct OddsPanel;
+
+#[derive(Component)]
+pub struct OddsToggleButton;
+
+#[derive(Component)]
+pub struct HintPanel;
+
+#[derive(Component)]
+pub struct HintToggleButton;
+
+#[derive(Component)]
+pub struct ObjectivesPanel;
+
+#[derive(Component)]
+pub struct BossBanner;
+
+#[derive(Component)]
+pub struct BlitzTimerDisplay;
+
+/// Side panel comparing the run with the personal best on the same seed code.
+#[derive(Component)]
+pub struct GhostPanel;
+
+#[derive(Component)]
+pub struct ReplayUI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayControl {
+    StepBack,
+    TogglePause,
+    StepForward,
+    Slower,
+    Faster,
+}
+
+#[derive(Component)]
+pub struct ReplayControlButton {
+    pub control: ReplayControl,
+}
+
+#[derive(Component)]
+pub struct ReplayStatus;
+
+/// The timeline bar; clicking or dragging along it jumps to that point of the run.
+#[derive(Component)]
+pub struct ReplayScrubber;
+
+#[derive(Component)]
+pub struct ReplayScrubberFill;
+
 #[derive(Component)]
 pub struct GameEndUI;
 
 #[derive(Component)]
-pub struct RestartButton;
\ No newline at end of file
+pub struct RestartButton;
+
+#[derive(Component)]
+pub struct WatchReplayButton;
\ No newline at end of file
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
-use super::{GameState, GameEndUI, RestartButton};
+use crate::game_state::{LastRun, LossReason, RunOutcome, RunSetup};
+use crate::persistence::{load_replay, replay_exists};
+use super::menu::button_colors;
+use super::playing::signed_delta;
+use super::{GameState, GameEndUI, RestartButton, WatchReplayButton};
 
-pub fn setup_game_won_ui(mut commands: Commands) {
+pub fn setup_game_won_ui(mut commands: Commands, last_run: Option<Res<LastRun>>) {
     commands.spawn((
         Node {
             width: Val::Percent(100.0),
@@ -29,12 +33,27 @@ pub fn setup_game_won_ui(mut commands: Commands) {
         ));
         
         parent.spawn((
-            Text::new("Congratulations! You reached the milestone!"),
+            Text::new("Congratulations! You cleared the final level!"),
             TextFont {
                 font_size: 24.0,
                 ..default()
             },
             TextColor(Color::WHITE),
+            Node {
+                margin: UiRect::bottom(Val::Px(10.0)),
+                ..default()
+            },
+        ));
+
+        spawn_record_banner(parent, last_run.as_deref());
+
+        parent.spawn((
+            Text::new(run_summary_text(last_run.as_deref())),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
             Node {
                 margin: UiRect::bottom(Val::Px(30.0)),
                 ..default()
@@ -65,10 +84,18 @@ pub fn setup_game_won_ui(mut commands: Commands) {
                 TextColor(Color::WHITE),
             ));
         });
+
+        spawn_watch_replay_button(parent, last_run.as_deref());
     });
 }
 
-pub fn setup_game_lost_ui(mut commands: Commands) {
+pub fn setup_game_lost_ui(mut commands: Commands, last_run: Option<Res<LastRun>>) {
+    let message = match last_run.as_ref().map(|run| run.outcome) {
+        Some(RunOutcome::Lost(LossReason::BagEmpty)) => "The bag ran out before the milestone!",
+        Some(RunOutcome::Lost(LossReason::Timeout)) => "You ran out of time!",
+        _ => "Your health reached zero!",
+    };
+
     commands.spawn((
         Node {
             width: Val::Percent(100.0),
@@ -96,12 +123,27 @@ pub fn setup_game_lost_ui(mut commands: Commands) {
         ));
         
         parent.spawn((
-            Text::new("Your health reached zero!"),
+            Text::new(message),
             TextFont {
                 font_size: 24.0,
                 ..default()
             },
             TextColor(Color::WHITE),
+            Node {
+                margin: UiRect::bottom(Val::Px(10.0)),
+                ..default()
+            },
+        ));
+
+        spawn_record_banner(parent, last_run.as_deref());
+
+        parent.spawn((
+            Text::new(run_summary_text(last_run.as_deref())),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
             Node {
                 margin: UiRect::bottom(Val::Px(30.0)),
                 ..default()
@@ -132,9 +174,88 @@ pub fn setup_game_lost_ui(mut commands: Commands) {
                 TextColor(Color::WHITE),
             ));
         });
+
+        spawn_watch_replay_button(parent, last_run.as_deref());
+    });
+}
+
+/// Gold call-out when the run made the leaderboard; nothing otherwise.
+fn spawn_record_banner(parent: &mut ChildSpawnerCommands, last_run: Option<&LastRun>) {
+    let Some(rank) = last_run.and_then(|run| run.high_score_rank) else {
+        return;
+    };
+
+    let text = match rank {
+        0 => "NEW RECORD!".to_string(),
+        rank => format!("New high score! #{}", rank + 1),
+    };
+
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.84, 0.0)),
+        Node {
+            margin: UiRect::bottom(Val::Px(10.0)),
+            ..default()
+        },
+    ));
+}
+
+/// Only offered once the run's replay has been written.
+fn spawn_watch_replay_button(parent: &mut ChildSpawnerCommands, last_run: Option<&LastRun>) {
+    if !last_run.is_some_and(|run| replay_exists(run.game_id)) {
+        return;
+    }
+
+    parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(200.0),
+            height: Val::Px(50.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            border: UiRect::all(Val::Px(2.0)),
+            margin: UiRect::top(Val::Px(12.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+        WatchReplayButton,
+    ))
+    .with_children(|button_parent| {
+        button_parent.spawn((
+            Text::new("WATCH REPLAY"),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
     });
 }
 
+fn run_summary_text(last_run: Option<&LastRun>) -> String {
+    let Some(run) = last_run else {
+        return String::new();
+    };
+
+    let mut text = format!("Score: {}   Game ID: {}\nSeed code: {}", run.score, run.game_id, run.seed_code);
+    match run.personal_best {
+        Some(best) if run.score > best => {
+            text.push_str(&format!("\nNew personal best for this seed! {} on the old best of {}", signed_delta(run.score, best), best));
+        }
+        Some(best) => text.push_str(&format!("\nPersonal best for this seed: {} ({})", best, signed_delta(run.score, best))),
+        None => {}
+    }
+    if run.practice {
+        text.push_str("\nPractice run: today's scored attempt was already used");
+    }
+    text
+}
+
 pub fn handle_restart_button(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &mut BorderColor),
@@ -143,19 +264,35 @@ pub fn handle_restart_button(
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     for (interaction, mut background_color, mut border_color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *background_color = BackgroundColor(Color::srgb(0.1, 0.1, 0.1));
-                *border_color = BorderColor(Color::srgb(0.3, 0.3, 0.3));
-                next_state.set(GameState::Menu);
-            }
-            Interaction::Hovered => {
-                *background_color = BackgroundColor(Color::srgb(0.3, 0.3, 0.3));
-                *border_color = BorderColor(Color::srgb(0.6, 0.6, 0.6));
-            }
-            Interaction::None => {
-                *background_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.2));
-                *border_color = BorderColor(Color::srgb(0.4, 0.4, 0.4));
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            next_state.set(GameState::Menu);
+        }
+    }
+}
+
+pub fn handle_watch_replay_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<WatchReplayButton>),
+    >,
+    last_run: Option<Res<LastRun>>,
+    mut run_setup: ResMut<RunSetup>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            let Some(run) = &last_run else {
+                continue;
+            };
+            match load_replay(run.game_id) {
+                Ok(Some(replay)) => {
+                    run_setup.replay = Some(replay);
+                    next_state.set(GameState::Replay);
+                }
+                Ok(None) => warn!("No replay saved for game {}", run.game_id),
+                Err(err) => warn!("Could not load replay: {}", err),
             }
         }
     }
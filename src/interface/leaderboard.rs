@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use crate::persistence::{HighScoreEntry, HighScores};
+use super::menu::{spawn_menu_button, spawn_tamper_warning};
+use super::{BackButton, LeaderboardUI};
+
+pub fn setup_leaderboard_ui(mut commands: Commands, high_scores: Res<HighScores>) {
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::BLACK),
+        LeaderboardUI,
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new("LEADERBOARD"),
+            TextFont {
+                font_size: 48.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+        ));
+
+        if high_scores.tampered {
+            spawn_tamper_warning(parent, "leaderboard");
+        }
+
+        let tables: Vec<String> = high_scores
+            .difficulties()
+            .into_iter()
+            .map(|difficulty| {
+                let rows = high_scores.ranked(difficulty).enumerate().map(|(rank, entry)| leaderboard_row(rank, entry));
+                std::iter::once(difficulty.to_string()).chain(rows).collect::<Vec<_>>().join("\n")
+            })
+            .collect();
+
+        parent.spawn((
+            Text::new(if tables.is_empty() { "No finished runs yet".to_string() } else { tables.join("\n\n") }),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+        ));
+
+        spawn_menu_button(parent, "BACK", BackButton);
+    });
+}
+
+fn leaderboard_row(rank: usize, entry: &HighScoreEntry) -> String {
+    format!(
+        "#{:<2}  {:>5} pts  Level {:<3}  Seed {:<18}  {}  {}",
+        rank + 1,
+        entry.score,
+        entry.level,
+        entry.seed_code().to_string(),
+        entry.game_id,
+        entry.date,
+    )
+}
+
+pub fn cleanup_leaderboard(mut commands: Commands, leaderboard_query: Query<Entity, With<LeaderboardUI>>) {
+    for entity in &leaderboard_query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -1,7 +1,23 @@
 use bevy::prelude::*;
-use super::{GameState, MenuUI, StartButton};
+use crate::game_state::{GameMode, RunSetup};
+use crate::persistence::{Profile, ProfileSlots, SavedRun};
+use crate::rules::{DailyChallenge, SeedCode, SeedCodeError};
+use super::text_input::{spawn_text_input, TextInput};
+use super::{
+    BackButton, ContinueButton, GameState, LeaderboardButton, MenuUI, ProfileButton, SeedCodeStatus, SeedInput,
+    StartButton,
+};
+
+/// Long enough for a daily code with its dashes.
+const SEED_CODE_INPUT_LIMIT: usize = 24;
+
+pub fn setup_menu_ui(mut commands: Commands, profile: Res<Profile>, slots: Res<ProfileSlots>) {
+    let daily = DailyChallenge::today();
+    let daily_label = match profile.daily_attempt(&daily.key()) {
+        Some(_) => "DAILY (PRACTICE)",
+        None => "DAILY",
+    };
 
-pub fn setup_menu_ui(mut commands: Commands) {
     commands.spawn((
         Node {
             width: Val::Percent(100.0),
@@ -23,61 +39,227 @@ pub fn setup_menu_ui(mut commands: Commands) {
             },
             TextColor(Color::WHITE),
             Node {
-                margin: UiRect::bottom(Val::Px(20.0)),
+                margin: UiRect::bottom(Val::Px(10.0)),
                 ..default()
             },
         ));
+
+        parent.spawn((
+            Text::new(format!("Playing as {}", slots.active_slot().name)),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+        ));
         
+        if SavedRun::exists(profile.slot) {
+            spawn_menu_button(parent, "CONTINUE", ContinueButton);
+        }
+
+        spawn_text_input(
+            parent,
+            TextInput::new("Seed code (optional)", SEED_CODE_INPUT_LIMIT, |c| c.is_ascii_alphanumeric() || c == '-' || c == ' '),
+            SeedInput,
+        );
         parent.spawn((
-            Button,
+            Text::new(""),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
             Node {
-                width: Val::Px(200.0),
-                height: Val::Px(60.0),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                margin: UiRect::top(Val::Px(20.0)),
-                border: UiRect::all(Val::Px(2.0)),
+                margin: UiRect::top(Val::Px(6.0)),
+                ..default()
+            },
+            SeedCodeStatus,
+        ));
+
+        spawn_menu_button(parent, "START", StartButton { mode: GameMode::Classic });
+        spawn_menu_button(parent, "BLITZ", StartButton { mode: GameMode::Blitz });
+        spawn_menu_button(parent, "ENDLESS", StartButton { mode: GameMode::Endless });
+        spawn_menu_button(parent, daily_label, StartButton { mode: GameMode::Daily });
+        spawn_menu_button(parent, "PROFILE", ProfileButton);
+        spawn_menu_button(parent, "LEADERBOARD", LeaderboardButton);
+    });
+}
+
+pub(super) fn spawn_menu_button(parent: &mut ChildSpawnerCommands, label: &str, marker: impl Bundle) {
+    parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(200.0),
+            height: Val::Px(50.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            margin: UiRect::top(Val::Px(12.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+        marker,
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 24.0,
                 ..default()
             },
-            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
-            BorderColor(Color::srgb(0.4, 0.4, 0.4)),
-            StartButton,
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                Text::new("START"),
-                TextFont {
-                    font_size: 24.0,
-                    ..default()
-                },
-                TextColor(Color::WHITE),
-            ));
-        });
+            TextColor(Color::WHITE),
+        ));
     });
 }
 
+/// The grey hover and press colours of [`spawn_menu_button`], shared by the other screens' buttons.
+pub(super) fn button_colors(interaction: Interaction, background_color: &mut BackgroundColor, border_color: &mut BorderColor) {
+    let (background, border) = match interaction {
+        Interaction::Pressed => (0.1, 0.3),
+        Interaction::Hovered => (0.3, 0.6),
+        Interaction::None => (0.2, 0.4),
+    };
+    *background_color = BackgroundColor(Color::srgb(background, background, background));
+    *border_color = BorderColor(Color::srgb(border, border, border));
+}
+
 pub fn handle_start_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor, &StartButton),
+        Changed<Interaction>,
+    >,
+    seed_input_query: Query<&TextInput, With<SeedInput>>,
+    mut run_setup: ResMut<RunSetup>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut background_color, mut border_color, start_button) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            // A mistyped code must not silently turn into a random run.
+            let Ok(seed) = typed_seed_code(&seed_input_query) else {
+                continue;
+            };
+            run_setup.mode = start_button.mode;
+            run_setup.resume = false;
+            run_setup.seed = seed;
+            next_state.set(GameState::Playing);
+        }
+    }
+}
+
+/// `Ok(None)` when the field is empty.
+fn typed_seed_code(seed_input_query: &Query<&TextInput, With<SeedInput>>) -> Result<Option<SeedCode>, SeedCodeError> {
+    match seed_input_query.iter().next() {
+        Some(input) if !input.value.trim().is_empty() => input.value.parse().map(Some),
+        _ => Ok(None),
+    }
+}
+
+pub fn update_seed_code_status(
+    seed_input_query: Query<&TextInput, (With<SeedInput>, Changed<TextInput>)>,
+    mut status_query: Query<&mut Text, With<SeedCodeStatus>>,
+) {
+    let Some(input) = seed_input_query.iter().next() else {
+        return;
+    };
+
+    let status = match input.value.trim() {
+        "" => "Leave empty for a random seed".to_string(),
+        typed => match typed.parse::<SeedCode>() {
+            Ok(code) if code.modifiers.is_empty() => format!("START, BLITZ and ENDLESS will play seed {}", code),
+            Ok(code) => format!(
+                "START, BLITZ and ENDLESS will play seed {}\n{}",
+                code,
+                code.modifiers.iter().map(|modifier| modifier.description()).collect::<Vec<_>>().join(", ")
+            ),
+            Err(err) => format!("Invalid seed code: {}", err),
+        },
+    };
+
+    for mut text in &mut status_query {
+        **text = status.clone();
+    }
+}
+
+/// Red notice shown on screens whose data failed its integrity check.
+pub(super) fn spawn_tamper_warning(parent: &mut ChildSpawnerCommands, what: &str) {
+    parent.spawn((
+        Text::new(format!(
+            "This {} failed its integrity check: it was edited outside the game, so it can't be trusted.",
+            what
+        )),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.2, 0.2)),
+        Node {
+            margin: UiRect::bottom(Val::Px(10.0)),
+            ..default()
+        },
+    ));
+}
+
+pub fn handle_continue_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<ContinueButton>),
+    >,
+    mut run_setup: ResMut<RunSetup>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            run_setup.resume = true;
+            next_state.set(GameState::Playing);
+        }
+    }
+}
+
+pub fn handle_profile_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<ProfileButton>),
+    >,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            next_state.set(GameState::Profile);
+        }
+    }
+}
+
+pub fn handle_leaderboard_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<LeaderboardButton>),
+    >,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            next_state.set(GameState::Leaderboard);
+        }
+    }
+}
+
+/// Shared by every screen reached from the menu.
+pub fn handle_back_button(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &mut BorderColor),
-        (Changed<Interaction>, With<StartButton>),
+        (Changed<Interaction>, With<BackButton>),
     >,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     for (interaction, mut background_color, mut border_color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *background_color = BackgroundColor(Color::srgb(0.1, 0.1, 0.1));
-                *border_color = BorderColor(Color::srgb(0.3, 0.3, 0.3));
-                next_state.set(GameState::Playing);
-            }
-            Interaction::Hovered => {
-                *background_color = BackgroundColor(Color::srgb(0.3, 0.3, 0.3));
-                *border_color = BorderColor(Color::srgb(0.6, 0.6, 0.6));
-            }
-            Interaction::None => {
-                *background_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.2));
-                *border_color = BorderColor(Color::srgb(0.4, 0.4, 0.4));
-            }
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            next_state.set(GameState::Menu);
         }
     }
 }
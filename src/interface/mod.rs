@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use crate::game_state::{check_replay_playback, drive_replay_playback, ReplayPlayback};
+use crate::persistence::{Profile, ProfileSlots};
 
 pub mod states;
 pub mod components;
@@ -6,24 +8,113 @@ pub mod camera;
 pub mod menu;
 pub mod playing;
 pub mod game_end;
+pub mod profile;
+pub mod leaderboard;
+pub mod replay;
+pub mod settings;
+pub mod text_input;
 
 pub use states::*;
 pub use components::*;
+pub use settings::*;
 
 pub struct InterfacePlugin;
 
 impl Plugin for InterfacePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
+            .insert_resource(UiSettings::load())
+            .init_resource::<profile::ProfileEditor>()
             .add_systems(Startup, camera::setup_camera)
+            .add_systems(
+                Update,
+                settings::save_settings.run_if(resource_changed::<UiSettings>.and(not(resource_added::<UiSettings>))),
+            )
+            .add_systems(
+                Update,
+                (text_input::focus_text_inputs, text_input::edit_text_inputs, text_input::update_text_inputs).chain(),
+            )
             .add_systems(OnEnter(GameState::Menu), menu::setup_menu_ui)
-            .add_systems(Update, menu::handle_start_button.run_if(in_state(GameState::Menu)))
+            .add_systems(
+                Update,
+                (
+                    menu::handle_start_button,
+                    menu::handle_continue_button,
+                    menu::handle_profile_button,
+                    menu::handle_leaderboard_button,
+                    menu::update_seed_code_status.after(text_input::edit_text_inputs),
+                )
+                    .run_if(in_state(GameState::Menu)),
+            )
             .add_systems(OnExit(GameState::Menu), menu::cleanup_menu)
+            .add_systems(OnEnter(GameState::Profile), profile::setup_profile_ui)
+            .add_systems(
+                Update,
+                (
+                    profile::finish_profile_rename.after(text_input::edit_text_inputs),
+                    profile::handle_profile_slot_buttons,
+                    profile::handle_new_profile_button,
+                    profile::refresh_profile_ui.run_if(
+                        resource_changed::<Profile>
+                            .or(resource_changed::<ProfileSlots>)
+                            .or(resource_changed::<profile::ProfileEditor>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Profile)),
+            )
+            .add_systems(OnExit(GameState::Profile), profile::cleanup_profile)
+            .add_systems(OnEnter(GameState::Leaderboard), leaderboard::setup_leaderboard_ui)
+            .add_systems(OnExit(GameState::Leaderboard), leaderboard::cleanup_leaderboard)
+            .add_systems(
+                Update,
+                menu::handle_back_button.run_if(in_state(GameState::Profile).or(in_state(GameState::Leaderboard))),
+            )
             .add_systems(OnEnter(GameState::Playing), playing::setup_playing_ui)
-            .add_systems(Update, (playing::handle_quit_button, playing::handle_pull_orb_button).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, playing::handle_pull_orb_button.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                (
+                    playing::handle_quit_button,
+                    playing::handle_odds_toggle_button,
+                    playing::handle_hint_toggle_button,
+                    playing::update_odds_panel,
+                    playing::update_hint_panel,
+                    playing::update_objectives_panel,
+                    playing::update_boss_treatment,
+                    playing::update_blitz_timer,
+                    playing::update_ghost_panel,
+                )
+                    .run_if(in_state(GameState::Playing).or(in_state(GameState::Replay))),
+            )
             .add_systems(OnExit(GameState::Playing), playing::cleanup_playing)
+            .add_systems(OnEnter(GameState::Replay), (playing::setup_playing_ui, replay::setup_replay_ui))
+            // Seeking must land before the next action is issued, or that action would be
+            // resolved against the state sought to.
+            .add_systems(
+                Update,
+                (
+                    replay::handle_replay_control_buttons,
+                    replay::handle_replay_keys,
+                    replay::handle_replay_scrubber,
+                )
+                    .chain()
+                    .before(drive_replay_playback)
+                    .run_if(in_state(GameState::Replay).and(resource_exists::<ReplayPlayback>)),
+            )
+            .add_systems(
+                Update,
+                replay::update_replay_ui
+                    .after(check_replay_playback)
+                    .run_if(in_state(GameState::Replay).and(resource_exists::<ReplayPlayback>)),
+            )
+            .add_systems(OnExit(GameState::Replay), (playing::cleanup_playing, replay::cleanup_replay_ui))
             .add_systems(OnEnter(GameState::GameWon), game_end::setup_game_won_ui)
-            .add_systems(Update, game_end::handle_restart_button.run_if(in_state(GameState::GameWon).or(in_state(GameState::GameLost))))
+            .add_systems(
+                Update,
+                (game_end::handle_restart_button, game_end::handle_watch_replay_button)
+                    .run_if(in_state(GameState::GameWon).or(in_state(GameState::GameLost))),
+            )
             .add_systems(OnExit(GameState::GameWon), game_end::cleanup_game_end)
             .add_systems(OnEnter(GameState::GameLost), game_end::setup_game_lost_ui)
             .add_systems(OnExit(GameState::GameLost), game_end::cleanup_game_end);
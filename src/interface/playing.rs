@@ -1,7 +1,14 @@
 use bevy::prelude::*;
-use super::{GameState, PlayingUI, QuitButton, PullOrbButton, StatDisplay, StatType};
+use crate::game_state::{ActiveRules, Ghost, PlayerGameState};
+use crate::rules::{Action, BossModifier, GameId, ObjectiveStatus, Solver};
+use super::menu::button_colors;
+use super::{
+    BlitzTimerDisplay, BossBanner, GameState, GhostPanel, HintPanel, HintToggleButton, ObjectivesPanel, OddsPanel, OddsToggleButton, PlayingUI,
+    QuitButton, PullOrbButton, StatDisplay, StatType, UiSettings,
+};
 
-pub fn setup_playing_ui(mut commands: Commands) {
+/// Also used by the replay viewer, which hides the pull button since the recording does the pulling.
+pub fn setup_playing_ui(mut commands: Commands, settings: Res<UiSettings>, state: Res<State<GameState>>) {
     commands.spawn((
         Node {
             width: Val::Percent(100.0),
@@ -15,6 +22,58 @@ pub fn setup_playing_ui(mut commands: Commands) {
         PlayingUI,
     ))
     .with_children(|parent| {
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.7, 0.7, 1.0)),
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                width: Val::Px(260.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BorderColor(Color::srgb(0.4, 0.4, 0.7)),
+            GhostPanel,
+        ));
+
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 28.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.3, 0.3)),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Node {
+                display: Display::None,
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+            BossBanner,
+        ));
+
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 28.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.9, 0.2)),
+            Node {
+                display: Display::None,
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+            BlitzTimerDisplay,
+        ));
+
         parent.spawn((
             Node {
                 flex_direction: FlexDirection::Column,
@@ -54,6 +113,16 @@ pub fn setup_playing_ui(mut commands: Commands) {
                 TextColor(Color::WHITE),
                 StatDisplay { stat_type: StatType::GameId },
             ));
+
+            stats_parent.spawn((
+                Text::new("Seed code: "),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                StatDisplay { stat_type: StatType::SeedCode },
+            ));
             
             stats_parent.spawn((
                 Text::new("Milestone: 15"),
@@ -106,6 +175,50 @@ pub fn setup_playing_ui(mut commands: Commands) {
             ));
         });
 
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            Node {
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+            ObjectivesPanel,
+        ));
+
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.8, 0.3)),
+            Node {
+                display: if settings.show_odds { Display::Flex } else { Display::None },
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+            OddsPanel,
+        ));
+
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.5, 0.8, 1.0)),
+            Node {
+                display: if settings.show_hint { Display::Flex } else { Display::None },
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+            HintPanel,
+        ));
+
         parent.spawn((
             Button,
             Node {
@@ -115,6 +228,7 @@ pub fn setup_playing_ui(mut commands: Commands) {
                 align_items: AlignItems::Center,
                 border: UiRect::all(Val::Px(2.0)),
                 margin: UiRect::bottom(Val::Px(20.0)),
+                display: if *state.get() == GameState::Replay { Display::None } else { Display::Flex },
                 ..default()
             },
             BackgroundColor(Color::srgb(0.2, 0.4, 0.2)),
@@ -125,7 +239,7 @@ pub fn setup_playing_ui(mut commands: Commands) {
             button_parent.spawn((
                 Text::new("PULL ORB"),
                 TextFont {
-                    font_size: 24.0,
+                    font_size: 18.0,
                     ..default()
                 },
                 TextColor(Color::WHITE),
@@ -156,6 +270,58 @@ pub fn setup_playing_ui(mut commands: Commands) {
                 TextColor(Color::WHITE),
             ));
         });
+
+        parent.spawn((
+            Button,
+            Node {
+                width: Val::Px(200.0),
+                height: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(2.0)),
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+            OddsToggleButton,
+        ))
+        .with_children(|button_parent| {
+            button_parent.spawn((
+                Text::new("ODDS"),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+
+        parent.spawn((
+            Button,
+            Node {
+                width: Val::Px(200.0),
+                height: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(2.0)),
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+            HintToggleButton,
+        ))
+        .with_children(|button_parent| {
+            button_parent.spawn((
+                Text::new("HINT"),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
     });
 }
 
@@ -167,20 +333,9 @@ pub fn handle_quit_button(
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     for (interaction, mut background_color, mut border_color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *background_color = BackgroundColor(Color::srgb(0.1, 0.1, 0.1));
-                *border_color = BorderColor(Color::srgb(0.3, 0.3, 0.3));
-                next_state.set(GameState::Menu);
-            }
-            Interaction::Hovered => {
-                *background_color = BackgroundColor(Color::srgb(0.3, 0.3, 0.3));
-                *border_color = BorderColor(Color::srgb(0.6, 0.6, 0.6));
-            }
-            Interaction::None => {
-                *background_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.2));
-                *border_color = BorderColor(Color::srgb(0.4, 0.4, 0.4));
-            }
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            next_state.set(GameState::Menu);
         }
     }
 }
@@ -190,17 +345,14 @@ pub fn handle_pull_orb_button(
         (&Interaction, &mut BackgroundColor, &mut BorderColor),
         (Changed<Interaction>, With<PullOrbButton>),
     >,
-    mut player_state: Option<ResMut<crate::game_state::PlayerGameState>>,
+    mut pull_requests: EventWriter<crate::game_state::PullOrbRequested>,
 ) {
     for (interaction, mut background_color, mut border_color) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
                 *background_color = BackgroundColor(Color::srgb(0.1, 0.2, 0.1));
                 *border_color = BorderColor(Color::srgb(0.2, 0.4, 0.2));
-                
-                if let Some(ref mut state) = player_state {
-                    state.pull_orb();
-                }
+                pull_requests.write(crate::game_state::PullOrbRequested);
             }
             Interaction::Hovered => {
                 *background_color = BackgroundColor(Color::srgb(0.3, 0.5, 0.3));
@@ -214,6 +366,246 @@ pub fn handle_pull_orb_button(
     }
 }
 
+pub fn handle_odds_toggle_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<OddsToggleButton>),
+    >,
+    mut settings: ResMut<UiSettings>,
+) {
+    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            settings.show_odds = !settings.show_odds;
+        }
+    }
+}
+
+pub fn handle_hint_toggle_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<HintToggleButton>),
+    >,
+    mut settings: ResMut<UiSettings>,
+) {
+    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            settings.show_hint = !settings.show_hint;
+        }
+    }
+}
+
+pub fn update_odds_panel(
+    player_state: Option<Res<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    settings: Res<UiSettings>,
+    mut panel_query: Query<(&mut Text, &mut Node), With<OddsPanel>>,
+) {
+    let Some(state) = player_state else {
+        return;
+    };
+    if !state.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for (mut text, mut node) in &mut panel_query {
+        node.display = if settings.show_odds { Display::Flex } else { Display::None };
+
+        **text = match state.next_pull_odds(&rules) {
+            Some(odds) => format!(
+                "Bomb: {:.0}%  Lethal: {:.0}%  Expected points: {:.1}",
+                odds.bomb_chance * 100.0,
+                odds.lethal_chance * 100.0,
+                odds.expected_points,
+            ),
+            None => "The bag is empty".to_string(),
+        };
+    }
+}
+
+pub fn update_hint_panel(
+    player_state: Option<Res<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    settings: Res<UiSettings>,
+    mut solver: Local<Option<(GameId, Solver)>>,
+    mut panel_query: Query<(&mut Text, &mut Node), With<HintPanel>>,
+) {
+    let Some(state) = player_state else {
+        return;
+    };
+    if !state.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    // A new run starts a new solver, so the memo doesn't pile up across runs.
+    let solver = match &mut *solver {
+        Some((game_id, solver)) if *game_id == state.game_id && solver.rules() == &**rules => solver,
+        slot => &mut slot.insert((state.game_id, Solver::new(&rules))).1,
+    };
+
+    for (mut text, mut node) in &mut panel_query {
+        node.display = if settings.show_hint { Display::Flex } else { Display::None };
+        if !settings.show_hint {
+            continue;
+        }
+
+        // The chance would give away how close the milestone is.
+        **text = if state.has_modifier(BossModifier::HiddenMilestone, &rules) {
+            "Hint: hidden along with the milestone".to_string()
+        } else {
+            let advice = solver.advise(&state);
+            match advice.best_action() {
+                Action::Pull => format!("Hint: pull, {:.0}% chance to clear this level", advice.pull_clear_chance * 100.0),
+                Action::Stop if advice.stop_clear_chance > 0.0 => "Hint: stop, this level is cleared".to_string(),
+                Action::Stop => "Hint: stop, this level can't be cleared any more".to_string(),
+            }
+        };
+    }
+}
+
+pub fn update_objectives_panel(
+    player_state: Option<Res<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    mut panel_query: Query<&mut Text, With<ObjectivesPanel>>,
+) {
+    let Some(state) = player_state else {
+        return;
+    };
+    if !state.is_changed() {
+        return;
+    }
+
+    let lines: Vec<String> = rules
+        .objectives_for_level(state.level)
+        .map(|objective| {
+            let marker = match objective.status(&state) {
+                ObjectiveStatus::OnTrack => "[+]",
+                ObjectiveStatus::Behind => "[ ]",
+                ObjectiveStatus::Failed => "[x]",
+            };
+            format!("{} {} - {}", marker, objective.description(), objective.progress(&state))
+        })
+        .collect();
+
+    for mut text in &mut panel_query {
+        **text = lines.join("\n");
+    }
+}
+
+pub fn update_boss_treatment(
+    player_state: Option<Res<PlayerGameState>>,
+    rules: Res<ActiveRules>,
+    mut root_query: Query<&mut BackgroundColor, With<PlayingUI>>,
+    mut banner_query: Query<(&mut Text, &mut Node), With<BossBanner>>,
+) {
+    let Some(state) = player_state else {
+        return;
+    };
+    if !state.is_changed() {
+        return;
+    }
+
+    let boss = rules.boss_for_level(state.level);
+
+    for mut background_color in &mut root_query {
+        *background_color = match boss {
+            Some(_) => BackgroundColor(Color::srgb(0.2, 0.0, 0.05)),
+            None => BackgroundColor(Color::BLACK),
+        };
+    }
+
+    let mut lines: Vec<String> = state
+        .modifiers
+        .iter()
+        .map(|modifier| format!("DAILY: {}", modifier.description()))
+        .collect();
+    if let Some(boss) = boss {
+        lines.push(format!("BOSS: {}\n{}", boss.name.to_uppercase(), boss.modifier.description()));
+    }
+
+    for (mut text, mut node) in &mut banner_query {
+        node.display = if lines.is_empty() { Display::None } else { Display::Flex };
+        **text = lines.join("\n");
+    }
+}
+
+pub fn update_blitz_timer(
+    player_state: Option<Res<PlayerGameState>>,
+    mut timer_query: Query<(&mut Text, &mut Node), With<BlitzTimerDisplay>>,
+) {
+    let Some(state) = player_state else {
+        return;
+    };
+    if !state.is_changed() {
+        return;
+    }
+
+    for (mut text, mut node) in &mut timer_query {
+        let Some(clock) = state.clock else {
+            node.display = Display::None;
+            continue;
+        };
+
+        node.display = Display::Flex;
+        **text = match (clock.level_remaining, clock.pull_remaining) {
+            (Some(level), Some(pull)) => format!("Time: {:.1}s   Next pull: {:.1}s", level, pull.max(0.0)),
+            (Some(level), None) => format!("Time: {:.1}s", level),
+            (None, Some(pull)) => format!("Next pull: {:.1}s", pull.max(0.0)),
+            (None, None) => String::new(),
+        };
+    }
+}
+
+pub fn update_ghost_panel(
+    player_state: Option<Res<PlayerGameState>>,
+    ghost: Option<Res<Ghost>>,
+    mut panel_query: Query<(&mut Text, &mut Node), With<GhostPanel>>,
+) {
+    let Some(state) = player_state else {
+        return;
+    };
+    if !state.is_changed() && !ghost.as_ref().is_some_and(|ghost| ghost.is_changed()) {
+        return;
+    }
+
+    for (mut text, mut node) in &mut panel_query {
+        let Some(ghost) = &ghost else {
+            node.display = Display::None;
+            continue;
+        };
+
+        node.display = Display::Flex;
+        let pulls = state.pulls.len();
+        let score = state.total_score();
+        **text = match ghost.at(pulls) {
+            Some(point) => format!(
+                "PERSONAL BEST {}\nAfter {} pulls:\nBest: {} pts, {} health\nYou:  {} pts, {} health\n{}",
+                ghost.score,
+                pulls,
+                point.score,
+                point.health,
+                score,
+                state.health,
+                signed_delta(score, point.score),
+            ),
+            None => format!(
+                "PERSONAL BEST {}\nThe best run ended after {} pulls\nYou: {} pts, {} health\n{} on its final score",
+                ghost.score,
+                ghost.pulls(),
+                score,
+                state.health,
+                signed_delta(score, ghost.score),
+            ),
+        };
+    }
+}
+
+/// e.g. `+12 pts` or `-3 pts`.
+pub(super) fn signed_delta(score: u32, best: u32) -> String {
+    format!("{:+} pts", score as i64 - best as i64)
+}
+
 pub fn cleanup_playing(mut commands: Commands, playing_query: Query<Entity, With<PlayingUI>>) {
     for entity in &playing_query {
         commands.entity(entity).despawn();
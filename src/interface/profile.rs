@@ -0,0 +1,305 @@
+use bevy::prelude::*;
+use crate::persistence::{LifetimeStats, Profile, ProfileSlots, PROFILE_NAME_LIMIT};
+use crate::rules::Orb;
+use super::menu::{button_colors, spawn_menu_button, spawn_tamper_warning};
+use super::text_input::{spawn_text_input, TextInput};
+use super::{BackButton, NewProfileButton, ProfileNameInput, ProfileSlotButton, ProfileUI, SlotAction};
+
+/// In-progress edits on the profile screen.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ProfileEditor {
+    /// Slot whose name is being typed into its [`ProfileNameInput`].
+    pub renaming: Option<u32>,
+    /// Slot whose DELETE was clicked once; a second click deletes it.
+    pub confirm_delete: Option<u32>,
+}
+
+pub fn setup_profile_ui(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    slots: Res<ProfileSlots>,
+    editor: Res<ProfileEditor>,
+) {
+    spawn_profile_ui(&mut commands, &profile, &slots, &editor);
+}
+
+/// The screen is small, so it's simply rebuilt whenever anything on it changes.
+pub fn refresh_profile_ui(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    slots: Res<ProfileSlots>,
+    editor: Res<ProfileEditor>,
+    profile_query: Query<Entity, With<ProfileUI>>,
+) {
+    for entity in &profile_query {
+        commands.entity(entity).despawn();
+    }
+    spawn_profile_ui(&mut commands, &profile, &slots, &editor);
+}
+
+fn spawn_profile_ui(commands: &mut Commands, profile: &Profile, slots: &ProfileSlots, editor: &ProfileEditor) {
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::BLACK),
+        ProfileUI,
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new("PROFILE"),
+            TextFont {
+                font_size: 48.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+        ));
+
+        if profile.tampered {
+            spawn_tamper_warning(parent, "profile");
+        }
+
+        parent.spawn((
+            Text::new(format!(
+                "{}   Moonrocks: {}   Cheddah: {}\n\n{}",
+                slots.active_slot().name,
+                profile.moonrocks,
+                profile.cheddah,
+                stats_text(&profile.stats)
+            )),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Node {
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            },
+        ));
+
+        for slot in &slots.slots {
+            let marker = if slot.id == slots.active { ">" } else { " " };
+            let delete_label = if editor.confirm_delete == Some(slot.id) { "SURE?" } else { "DELETE" };
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::top(Val::Px(6.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    if editor.renaming == Some(slot.id) {
+                        let input = TextInput {
+                            value: slot.name.clone(),
+                            focused: true,
+                            ..TextInput::new("Profile name", PROFILE_NAME_LIMIT, |c| !c.is_control())
+                        };
+                        spawn_text_input(row, input, ProfileNameInput { slot: slot.id });
+                    } else {
+                        row.spawn((
+                            Text::new(format!("{} {}", marker, slot.name)),
+                            TextFont {
+                                font_size: 20.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                            Node {
+                                width: Val::Px(220.0),
+                                ..default()
+                            },
+                        ));
+                    }
+                    spawn_slot_button(row, "SELECT", ProfileSlotButton { slot: slot.id, action: SlotAction::Select });
+                    spawn_slot_button(row, "RENAME", ProfileSlotButton { slot: slot.id, action: SlotAction::Rename });
+                    spawn_slot_button(row, delete_label, ProfileSlotButton { slot: slot.id, action: SlotAction::Delete });
+                });
+        }
+
+        if editor.renaming.is_some() {
+            parent.spawn((
+                Text::new("Type a name, ENTER to save, ESC to cancel"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                Node {
+                    margin: UiRect::top(Val::Px(6.0)),
+                    ..default()
+                },
+            ));
+        }
+
+        if !slots.is_full() {
+            spawn_menu_button(parent, "NEW PROFILE", NewProfileButton);
+        }
+        spawn_menu_button(parent, "BACK", BackButton);
+    });
+}
+
+fn spawn_slot_button(parent: &mut ChildSpawnerCommands, label: &str, marker: ProfileSlotButton) {
+    parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(90.0),
+            height: Val::Px(32.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            margin: UiRect::left(Val::Px(8.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+        marker,
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+fn stats_text(stats: &LifetimeStats) -> String {
+    let orbs: Vec<String> = Orb::ALL
+        .iter()
+        .map(|&orb| format!("{:?}: {}", orb, stats.orbs_pulled(orb)))
+        .collect();
+
+    [
+        format!("Runs started: {}", stats.runs_started),
+        format!("Wins: {}   Losses: {}", stats.wins, stats.losses),
+        format!("Total pulls: {}", stats.total_pulls),
+        format!("Orbs pulled - {}", orbs.join("  ")),
+        format!("Best score: {}", stats.best_score),
+        format!("Highest level: {}", stats.highest_level),
+        format!("Moonrocks earned: {}", stats.moonrocks_earned),
+    ]
+    .join("\n")
+}
+
+pub fn handle_profile_slot_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor, &ProfileSlotButton),
+        Changed<Interaction>,
+    >,
+    mut slots: ResMut<ProfileSlots>,
+    mut profile: ResMut<Profile>,
+    mut editor: ResMut<ProfileEditor>,
+) {
+    for (interaction, mut background_color, mut border_color, button) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            apply_slot_action(button, &mut slots, &mut profile, &mut editor);
+        }
+    }
+}
+
+fn apply_slot_action(
+    button: &ProfileSlotButton,
+    slots: &mut ProfileSlots,
+    profile: &mut Profile,
+    editor: &mut ProfileEditor,
+) {
+    match button.action {
+        SlotAction::Select => {
+            *editor = ProfileEditor::default();
+            if slots.select(button.slot) {
+                save_slots(slots);
+                *profile = Profile::load(button.slot);
+            }
+        }
+        SlotAction::Rename => {
+            *editor = ProfileEditor { renaming: Some(button.slot), ..default() };
+        }
+        SlotAction::Delete if editor.confirm_delete == Some(button.slot) => {
+            *editor = ProfileEditor::default();
+            if let Err(err) = slots.delete(button.slot) {
+                warn!("Could not delete profile {}: {}", button.slot, err);
+            }
+            save_slots(slots);
+            if profile.slot != slots.active {
+                *profile = Profile::load(slots.active);
+            }
+        }
+        SlotAction::Delete => {
+            *editor = ProfileEditor { confirm_delete: Some(button.slot), ..default() };
+        }
+    }
+}
+
+fn save_slots(slots: &ProfileSlots) {
+    if let Err(err) = slots.save() {
+        warn!("Could not save profile list: {}", err);
+    }
+}
+
+pub fn handle_new_profile_button(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<NewProfileButton>),
+    >,
+    mut slots: ResMut<ProfileSlots>,
+    mut profile: ResMut<Profile>,
+    mut editor: ResMut<ProfileEditor>,
+) {
+    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed
+            && let Some(id) = slots.create("")
+        {
+            slots.select(id);
+            save_slots(&slots);
+            *profile = Profile::load(id);
+            *editor = ProfileEditor { renaming: Some(id), ..default() };
+        }
+    }
+}
+
+/// Saves the typed name once the rename field is submitted with Enter. Escape or a click
+/// elsewhere drops the edit.
+pub fn finish_profile_rename(
+    input_query: Query<(&TextInput, &ProfileNameInput), Changed<TextInput>>,
+    mut slots: ResMut<ProfileSlots>,
+    mut editor: ResMut<ProfileEditor>,
+) {
+    for (input, name_input) in &input_query {
+        if input.focused || editor.renaming != Some(name_input.slot) {
+            continue;
+        }
+
+        if input.submitted {
+            slots.rename(name_input.slot, &input.value);
+            save_slots(&slots);
+        }
+        editor.renaming = None;
+    }
+}
+
+pub fn cleanup_profile(
+    mut commands: Commands,
+    mut editor: ResMut<ProfileEditor>,
+    profile_query: Query<Entity, With<ProfileUI>>,
+) {
+    *editor = ProfileEditor::default();
+    for entity in &profile_query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use crate::game_state::{ActiveRules, PlayerGameState, ReplayPlayback};
+use crate::rules::{LossReason, RulesConfig, RunOutcome};
+use super::menu::button_colors;
+use super::{ReplayControl, ReplayControlButton, ReplayScrubber, ReplayScrubberFill, ReplayStatus, ReplayUI};
+
+/// Spawned alongside the playing UI, which shows the replayed state as it would look in a run.
+pub fn setup_replay_ui(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(12.0)),
+            row_gap: Val::Px(8.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        ReplayUI,
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.5, 0.8, 1.0)),
+            TextLayout::new_with_justify(JustifyText::Center),
+            ReplayStatus,
+        ));
+
+        parent.spawn((
+            Button,
+            Node {
+                width: Val::Percent(80.0),
+                height: Val::Px(16.0),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+            BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+            RelativeCursorPosition::default(),
+            ReplayScrubber,
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.3, 0.6, 0.9)),
+                ReplayScrubberFill,
+            ));
+        });
+
+        parent.spawn(Node {
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(8.0),
+            ..default()
+        })
+        .with_children(|row| {
+            spawn_replay_button(row, "SLOWER", ReplayControl::Slower);
+            spawn_replay_button(row, "< STEP", ReplayControl::StepBack);
+            spawn_replay_button(row, "PAUSE", ReplayControl::TogglePause);
+            spawn_replay_button(row, "STEP >", ReplayControl::StepForward);
+            spawn_replay_button(row, "FASTER", ReplayControl::Faster);
+        });
+    });
+}
+
+fn spawn_replay_button(parent: &mut ChildSpawnerCommands, label: &str, control: ReplayControl) {
+    parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(110.0),
+            height: Val::Px(40.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+        ReplayControlButton { control },
+    ))
+    .with_children(|button_parent| {
+        button_parent.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+pub fn handle_replay_control_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor, &ReplayControlButton),
+        Changed<Interaction>,
+    >,
+    mut playback: ResMut<ReplayPlayback>,
+    mut player_state: ResMut<PlayerGameState>,
+    rules: Res<ActiveRules>,
+) {
+    for (interaction, mut background_color, mut border_color, button) in &mut interaction_query {
+        button_colors(*interaction, &mut background_color, &mut border_color);
+        if *interaction == Interaction::Pressed {
+            apply_replay_control(button.control, &mut playback, &mut player_state, &rules);
+        }
+    }
+}
+
+/// Space pauses, the arrow keys step and `-` / `=` change speed.
+pub fn handle_replay_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut player_state: ResMut<PlayerGameState>,
+    rules: Res<ActiveRules>,
+) {
+    let bindings = [
+        (KeyCode::Space, ReplayControl::TogglePause),
+        (KeyCode::ArrowLeft, ReplayControl::StepBack),
+        (KeyCode::ArrowRight, ReplayControl::StepForward),
+        (KeyCode::Minus, ReplayControl::Slower),
+        (KeyCode::Equal, ReplayControl::Faster),
+    ];
+
+    for (key, control) in bindings {
+        if keys.just_pressed(key) {
+            apply_replay_control(control, &mut playback, &mut player_state, &rules);
+        }
+    }
+}
+
+fn apply_replay_control(
+    control: ReplayControl,
+    playback: &mut ReplayPlayback,
+    state: &mut PlayerGameState,
+    rules: &RulesConfig,
+) {
+    match control {
+        ReplayControl::StepBack => playback.step_back(state, rules),
+        ReplayControl::StepForward => playback.step_forward(),
+        ReplayControl::Slower => playback.slower(),
+        ReplayControl::Faster => playback.faster(),
+        // Play from the top again once the end has been reached.
+        ReplayControl::TogglePause if playback.paused && playback.is_finished() => {
+            playback.seek(0, state, rules);
+            playback.paused = false;
+        }
+        ReplayControl::TogglePause => playback.paused = !playback.paused,
+    }
+}
+
+/// Holding the mouse down on the timeline scrubs through the run.
+pub fn handle_replay_scrubber(
+    scrubber_query: Query<(&Interaction, &RelativeCursorPosition), With<ReplayScrubber>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut player_state: ResMut<PlayerGameState>,
+    rules: Res<ActiveRules>,
+) {
+    for (interaction, cursor) in &scrubber_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+
+        let step = (position.x.clamp(0.0, 1.0) * playback.replay.len() as f32).round() as usize;
+        if step != playback.position {
+            playback.paused = true;
+            playback.seek(step, &mut player_state, &rules);
+        }
+    }
+}
+
+pub fn update_replay_ui(
+    playback: Res<ReplayPlayback>,
+    player_state: Res<PlayerGameState>,
+    mut status_query: Query<&mut Text, With<ReplayStatus>>,
+    mut fill_query: Query<&mut Node, With<ReplayScrubberFill>>,
+    mut button_query: Query<(&ReplayControlButton, &Children)>,
+    mut label_query: Query<&mut Text, Without<ReplayStatus>>,
+) {
+    if !playback.is_changed() && !player_state.is_changed() {
+        return;
+    }
+
+    let total = playback.replay.len();
+    let mut status = format!(
+        "REPLAY  Game {}   Step {}/{}   {}x   {}",
+        playback.replay.game_id,
+        playback.position,
+        total,
+        playback.speed_multiplier(),
+        if playback.paused { "PAUSED" } else { "PLAYING" },
+    );
+    if playback.is_finished() {
+        status.push_str(&format!("\n{}", outcome_text(player_state.outcome())));
+    }
+    if let Some(mismatch) = playback.mismatch {
+        status.push_str(&format!("\nWARNING: {}", mismatch));
+    }
+
+    for mut text in &mut status_query {
+        **text = status.clone();
+    }
+
+    let progress = if total == 0 { 100.0 } else { playback.position as f32 / total as f32 * 100.0 };
+    for mut node in &mut fill_query {
+        node.width = Val::Percent(progress);
+    }
+
+    let pause_label = if playback.paused { "PLAY" } else { "PAUSE" };
+    for (button, children) in &mut button_query {
+        if button.control != ReplayControl::TogglePause {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = label_query.get_mut(child) {
+                **text = pause_label.to_string();
+            }
+        }
+    }
+}
+
+fn outcome_text(outcome: Option<RunOutcome>) -> &'static str {
+    match outcome {
+        Some(RunOutcome::Won) => "End of replay: the run was won",
+        Some(RunOutcome::Lost(LossReason::HealthDepleted)) => "End of replay: health reached zero",
+        Some(RunOutcome::Lost(LossReason::BagEmpty)) => "End of replay: the bag ran out",
+        Some(RunOutcome::Lost(LossReason::Timeout)) => "End of replay: time ran out",
+        None => "End of replay: the run was abandoned here",
+    }
+}
+
+pub fn cleanup_replay_ui(mut commands: Commands, replay_query: Query<Entity, With<ReplayUI>>) {
+    for entity in &replay_query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::persistence::{self, Versioned};
+
+/// Player-facing display preferences, kept between sessions.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    pub show_odds: bool,
+    pub show_hint: bool,
+}
+
+impl Versioned for UiSettings {
+    const FILE: &'static str = "settings.json";
+    const VERSION: u32 = 1;
+}
+
+impl UiSettings {
+    pub fn load() -> Self {
+        persistence::load_versioned()
+            .unwrap_or_else(|err| {
+                warn!("Could not read settings, using defaults: {}", err);
+                None
+            })
+            .unwrap_or_default()
+    }
+}
+
+pub fn save_settings(settings: Res<UiSettings>) {
+    if let Err(err) = persistence::save_versioned(&*settings) {
+        warn!("Could not save settings: {}", err);
+    }
+}
@@ -4,9 +4,13 @@ use bevy::prelude::*;
 pub enum GameState {
     #[default]
     Menu,
+    Profile,
+    Leaderboard,
     Playing,
     GameWon,
     GameLost,
+    /// Watching a recorded run in the playing UI.
+    Replay,
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq)]
@@ -14,6 +18,7 @@ pub enum StatType {
     Health,
     Points,
     GameId,
+    SeedCode,
     Milestone,
     Orbs,
     Level,
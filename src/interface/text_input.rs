@@ -0,0 +1,146 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+
+/// A single-line text field. Clicking it takes keyboard focus, clicking anywhere else or
+/// pressing Enter / Escape gives it up.
+#[derive(Component, Debug, Clone)]
+pub struct TextInput {
+    pub value: String,
+    /// Shown greyed out while the field is empty.
+    pub placeholder: String,
+    /// In characters.
+    pub max_len: usize,
+    /// Which typed characters are kept.
+    pub accepts: fn(char) -> bool,
+    pub focused: bool,
+    /// Set when Enter gave up focus, rather than Escape or a click elsewhere.
+    /// Cleared when the field is focused again.
+    pub submitted: bool,
+}
+
+impl TextInput {
+    pub fn new(placeholder: &str, max_len: usize, accepts: fn(char) -> bool) -> Self {
+        Self {
+            value: String::new(),
+            placeholder: placeholder.to_string(),
+            max_len,
+            accepts,
+            focused: false,
+            submitted: false,
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        if (self.accepts)(c) && self.value.chars().count() < self.max_len {
+            self.value.push(c);
+        }
+    }
+}
+
+pub fn spawn_text_input(parent: &mut ChildSpawnerCommands, input: TextInput, marker: impl Bundle) {
+    parent.spawn((
+        Button,
+        Node {
+            width: Val::Px(260.0),
+            height: Val::Px(44.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            margin: UiRect::top(Val::Px(12.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+        BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+        input,
+        marker,
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+    });
+}
+
+/// Runs on the frame of a click, after UI picking has set `Interaction`.
+pub fn focus_text_inputs(mouse: Res<ButtonInput<MouseButton>>, mut input_query: Query<(&Interaction, &mut TextInput)>) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    for (interaction, mut input) in &mut input_query {
+        let focused = *interaction == Interaction::Pressed;
+        if input.focused != focused {
+            input.focused = focused;
+            if focused {
+                input.submitted = false;
+            }
+        }
+    }
+}
+
+pub fn edit_text_inputs(mut keyboard_input: EventReader<KeyboardInput>, mut input_query: Query<&mut TextInput>) {
+    let Some(mut input) = input_query.iter_mut().find(|input| input.focused) else {
+        keyboard_input.clear();
+        return;
+    };
+
+    for event in keyboard_input.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Enter => {
+                input.focused = false;
+                input.submitted = true;
+                return;
+            }
+            Key::Escape => {
+                input.focused = false;
+                return;
+            }
+            Key::Backspace => {
+                input.value.pop();
+            }
+            Key::Space => input.push(' '),
+            Key::Character(text) => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    input.push(c);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn update_text_inputs(
+    mut input_query: Query<(&TextInput, &Children, &mut BorderColor), Changed<TextInput>>,
+    mut text_query: Query<(&mut Text, &mut TextColor)>,
+) {
+    for (input, children, mut border_color) in &mut input_query {
+        *border_color = if input.focused {
+            BorderColor(Color::srgb(0.5, 0.8, 1.0))
+        } else {
+            BorderColor(Color::srgb(0.4, 0.4, 0.4))
+        };
+
+        let (content, color) = match (input.value.is_empty(), input.focused) {
+            (true, false) => (input.placeholder.clone(), Color::srgb(0.5, 0.5, 0.5)),
+            (_, true) => (format!("{}_", input.value), Color::WHITE),
+            (false, false) => (input.value.clone(), Color::WHITE),
+        };
+
+        for child in children.iter() {
+            if let Ok((mut text, mut text_color)) = text_query.get_mut(child) {
+                **text = content.clone();
+                *text_color = TextColor(color);
+            }
+        }
+    }
+}
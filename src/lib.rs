@@ -1,7 +1,9 @@
 use bevy::{asset::AssetMetaCheck, prelude::*};
 
 mod interface;
-mod game_state;
+pub mod game_state;
+pub mod persistence;
+pub use glitchbomb_rules as rules;
 
 use interface::InterfacePlugin;
 use game_state::GameStatePlugin;
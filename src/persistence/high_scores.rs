@@ -0,0 +1,178 @@
+use std::fmt;
+use std::io;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{BossModifier, GameId, GameMode, SeedCode};
+use super::Versioned;
+
+/// How many entries the table keeps for each difficulty.
+pub const HIGH_SCORE_LIMIT: usize = 10;
+
+/// What a run is ranked against: runs of the same mode under the same rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difficulty {
+    pub mode: GameMode,
+    /// [`RulesConfig::fingerprint`](crate::rules::RulesConfig::fingerprint) of the rules played under.
+    pub rules_hash: u32,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, rules {:08X}", self.mode.name(), self.rules_hash)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub score: u32,
+    pub level: u32,
+    pub game_id: GameId,
+    pub mode: GameMode,
+    pub rules_hash: u32,
+    pub seed: u64,
+    /// Run-wide modifiers from the run's seed code.
+    pub modifiers: Vec<BossModifier>,
+    /// Local date the run finished, `YYYY-MM-DD`.
+    pub date: String,
+}
+
+impl HighScoreEntry {
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty { mode: self.mode, rules_hash: self.rules_hash }
+    }
+
+    pub fn seed_code(&self) -> SeedCode {
+        SeedCode { seed: self.seed, modifiers: self.modifiers.clone() }
+    }
+}
+
+/// Best finished runs on this machine, highest score first. Each difficulty is ranked
+/// on its own, so runs under easier rules can't push harder ones off the table.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+    /// Set once the file fails its integrity check; see `Profile::tampered`.
+    #[serde(default)]
+    pub tampered: bool,
+}
+
+impl Versioned for HighScores {
+    const FILE: &'static str = "high_scores.json";
+    const VERSION: u32 = 1;
+    const SIGNED_SINCE: Option<u32> = Some(1);
+
+    fn mark_tampered(&mut self) {
+        self.tampered = true;
+    }
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        super::load_versioned()
+            .unwrap_or_else(|err| {
+                warn!("Could not read high scores, starting empty: {}", err);
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::save_versioned(self)
+    }
+
+    pub fn best(&self) -> Option<&HighScoreEntry> {
+        self.entries.first()
+    }
+
+    /// The entries of one difficulty, highest score first.
+    pub fn ranked(&self, difficulty: Difficulty) -> impl Iterator<Item = &HighScoreEntry> {
+        self.entries.iter().filter(move |entry| entry.difficulty() == difficulty)
+    }
+
+    /// Every difficulty with an entry, the one holding the top score first.
+    pub fn difficulties(&self) -> Vec<Difficulty> {
+        let mut difficulties: Vec<Difficulty> = Vec::new();
+        for entry in &self.entries {
+            if !difficulties.contains(&entry.difficulty()) {
+                difficulties.push(entry.difficulty());
+            }
+        }
+        difficulties
+    }
+
+    /// Inserts the entry in rank order and returns its 0-based rank within its
+    /// difficulty, or `None` if it didn't make the table. Ties go to the earlier run.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let difficulty = entry.difficulty();
+        let rank = self.ranked(difficulty).filter(|existing| existing.score >= entry.score).count();
+        if rank >= HIGH_SCORE_LIMIT {
+            return None;
+        }
+
+        let position = self.entries.iter().position(|existing| entry.score > existing.score).unwrap_or(self.entries.len());
+        self.entries.insert(position, entry);
+        if let Some(dropped) = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, existing)| existing.difficulty() == difficulty)
+            .nth(HIGH_SCORE_LIMIT)
+            .map(|(index, _)| index)
+        {
+            self.entries.remove(dropped);
+        }
+        Some(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{load_versioned, save_versioned, storage, with_memory_storage};
+    use serde_json::Value;
+
+    fn entry(score: u32, rules_hash: u32) -> HighScoreEntry {
+        HighScoreEntry {
+            score,
+            level: 1,
+            game_id: GameId::new(u64::from(score), 42),
+            mode: GameMode::Classic,
+            rules_hash,
+            seed: 42,
+            modifiers: Vec::new(),
+            date: "2026-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn each_difficulty_is_ranked_on_its_own() {
+        let mut scores = HighScores::default();
+        for score in 1..=HIGH_SCORE_LIMIT as u32 {
+            assert!(scores.insert(entry(score * 10, 1)).is_some());
+        }
+        assert_eq!(scores.insert(entry(5, 1)), None);
+
+        // A low score under other rules still tops its own table.
+        assert_eq!(scores.insert(entry(5, 2)), Some(0));
+        assert_eq!(scores.insert(entry(45, 1)), Some(6));
+        assert_eq!(scores.ranked(entry(0, 1).difficulty()).count(), HIGH_SCORE_LIMIT);
+        assert_eq!(scores.difficulties(), [entry(0, 1).difficulty(), entry(0, 2).difficulty()]);
+    }
+
+    #[test]
+    fn edited_scores_are_marked_tampered() {
+        with_memory_storage(|| {
+            let mut scores = HighScores::default();
+            scores.insert(entry(40, 1));
+            save_versioned(&scores).unwrap();
+            assert!(!load_versioned::<HighScores>().unwrap().unwrap().tampered);
+
+            let mut data: Value = serde_json::from_slice(&storage().read(HighScores::FILE).unwrap().unwrap()).unwrap();
+            data["entries"][0]["score"] = 400.into();
+            storage().write(HighScores::FILE, &serde_json::to_vec(&data).unwrap()).unwrap();
+            assert!(load_versioned::<HighScores>().unwrap().unwrap().tampered);
+        });
+    }
+}
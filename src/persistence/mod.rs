@@ -0,0 +1,68 @@
+//! Small files the game keeps between sessions, stored as JSON in whichever
+//! [`Storage`] backend is active: the platform data directory by default.
+
+use std::io;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+pub mod high_scores;
+pub mod profile;
+pub mod profile_slots;
+pub mod replays;
+pub mod run_counter;
+pub mod run_history;
+pub mod saved_run;
+pub mod signing;
+pub mod storage;
+pub mod versioned;
+
+pub use high_scores::*;
+pub use profile::*;
+pub use profile_slots::*;
+pub use replays::*;
+pub use run_counter::*;
+pub use run_history::*;
+pub use saved_run::*;
+pub use storage::*;
+pub use versioned::*;
+
+/// Overrides the data directory, e.g. to keep playtest or CI data separate.
+pub const DATA_DIR_ENV: &str = "GLITCHBOMB_DATA_DIR";
+
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+
+    dirs::data_dir().map(|dir| dir.join("glitchbomb"))
+}
+
+pub fn write_json<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    let contents = serde_json::to_vec_pretty(value)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    storage().write(name, &contents)
+}
+
+pub fn write_json_compact<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    let contents = serde_json::to_vec(value)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    storage().write(name, &contents)
+}
+
+/// Appends `value` as one line of JSON, creating the file if needed.
+pub fn append_json_line<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    let mut line = serde_json::to_vec(value)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    line.push(b'\n');
+    storage().append(name, &line)
+}
+
+pub fn file_exists(name: &str) -> bool {
+    storage().exists(name)
+}
+
+/// Deleting a file that isn't there is fine.
+pub fn remove_file(name: &str) -> io::Result<()> {
+    storage().remove(name)
+}
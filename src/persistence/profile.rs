@@ -0,0 +1,206 @@
+use std::io;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{BossModifier, GameId, GameMode, Orb, RunOutcome, SeedCode};
+use super::{slot_key, Versioned};
+
+/// The scored daily challenge attempt for one date. It's recorded when the run starts,
+/// so quitting early still uses up the day's attempt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyAttempt {
+    /// `YYYY-MM-DD`, see `DailyChallenge::key`.
+    pub date: String,
+    pub game_id: GameId,
+    /// Filled in when the run ends.
+    pub score: Option<u32>,
+    pub outcome: Option<RunOutcome>,
+}
+
+/// The best finished run on one seed code in one mode. Its replay is the ghost that later
+/// runs on the same code are compared against.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersonalBest {
+    pub mode: GameMode,
+    pub seed: u64,
+    pub modifiers: Vec<BossModifier>,
+    pub game_id: GameId,
+    pub score: u32,
+}
+
+impl PersonalBest {
+    fn matches(&self, mode: GameMode, code: &SeedCode) -> bool {
+        self.mode == mode && self.seed == code.seed && self.modifiers == code.modifiers
+    }
+}
+
+/// Totals across every run this profile has played.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    /// Counted when a run starts, so abandoned runs are included.
+    pub runs_started: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub total_pulls: u32,
+    pub health_orbs_pulled: u32,
+    pub point_orbs_pulled: u32,
+    pub bomb_orbs_pulled: u32,
+    pub best_score: u32,
+    pub highest_level: u32,
+    pub moonrocks_earned: u32,
+}
+
+impl LifetimeStats {
+    pub fn record_run(&mut self, outcome: RunOutcome, score: u32, level: u32, moonrocks: u32, pulls: &[Orb]) {
+        if outcome.is_win() {
+            self.wins += 1;
+        } else {
+            self.losses += 1;
+        }
+
+        self.total_pulls += pulls.len() as u32;
+        for orb in pulls {
+            match orb {
+                Orb::Health => self.health_orbs_pulled += 1,
+                Orb::Point => self.point_orbs_pulled += 1,
+                Orb::Bomb => self.bomb_orbs_pulled += 1,
+            }
+        }
+
+        self.best_score = self.best_score.max(score);
+        self.highest_level = self.highest_level.max(level);
+        self.moonrocks_earned += moonrocks;
+    }
+
+    pub fn orbs_pulled(&self, orb: Orb) -> u32 {
+        match orb {
+            Orb::Health => self.health_orbs_pulled,
+            Orb::Point => self.point_orbs_pulled,
+            Orb::Bomb => self.bomb_orbs_pulled,
+        }
+    }
+}
+
+/// What the game remembers about one player between sessions. Each profile slot has
+/// its own; the `Profile` resource is the active slot's.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// The slot this profile belongs to; set on load rather than saved.
+    #[serde(skip)]
+    pub slot: u32,
+    /// Balances carried from run to run. A run starts with them and hands them back when it ends.
+    pub moonrocks: u32,
+    pub cheddah: u32,
+    pub stats: LifetimeStats,
+    pub daily_attempts: Vec<DailyAttempt>,
+    pub personal_bests: Vec<PersonalBest>,
+    /// Set once the file fails its integrity check. It's saved and signed with the rest,
+    /// so re-saving doesn't clear it.
+    pub tampered: bool,
+}
+
+/// `FILE` is the name from before profile slots; each slot saves to `slot_key(FILE, slot)`.
+impl Versioned for Profile {
+    const FILE: &'static str = "profile.json";
+    const VERSION: u32 = 2;
+    const SIGNED_SINCE: Option<u32> = Some(2);
+
+    fn mark_tampered(&mut self) {
+        self.tampered = true;
+    }
+}
+
+impl Profile {
+    pub fn load(slot: u32) -> Self {
+        let profile: Profile = super::load_versioned_from(&slot_key(Self::FILE, slot))
+            .unwrap_or_else(|err| {
+                warn!("Could not read profile, starting fresh: {}", err);
+                None
+            })
+            .unwrap_or_default();
+
+        Self { slot, ..profile }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::save_versioned_to(&slot_key(Self::FILE, self.slot), self)
+    }
+
+    pub fn daily_attempt(&self, date: &str) -> Option<&DailyAttempt> {
+        self.daily_attempts.iter().find(|attempt| attempt.date == date)
+    }
+
+    /// Claims the scored attempt for `date`. Returns `false` if it was already used,
+    /// in which case the run is only practice.
+    pub fn start_daily_attempt(&mut self, date: String, game_id: GameId) -> bool {
+        if self.daily_attempt(&date).is_some() {
+            return false;
+        }
+
+        self.daily_attempts.push(DailyAttempt { date, game_id, score: None, outcome: None });
+        true
+    }
+
+    pub fn personal_best(&self, mode: GameMode, code: &SeedCode) -> Option<&PersonalBest> {
+        self.personal_bests.iter().find(|best| best.matches(mode, code))
+    }
+
+    /// Keeps the run if it beats the current best for its mode and code; returns whether it did.
+    /// A tie keeps the earlier run.
+    pub fn record_personal_best(&mut self, mode: GameMode, code: &SeedCode, game_id: GameId, score: u32) -> bool {
+        let run = PersonalBest { mode, seed: code.seed, modifiers: code.modifiers.clone(), game_id, score };
+        match self.personal_bests.iter_mut().find(|best| best.matches(mode, code)) {
+            Some(current) if current.score >= score => return false,
+            Some(current) => *current = run,
+            None => self.personal_bests.push(run),
+        }
+        true
+    }
+
+    /// Records the result if `game_id` is a scored attempt; returns whether it was.
+    pub fn finish_daily_attempt(&mut self, game_id: GameId, score: u32, outcome: RunOutcome) -> bool {
+        let Some(attempt) = self.daily_attempts.iter_mut().find(|attempt| attempt.game_id == game_id) else {
+            return false;
+        };
+
+        attempt.score = Some(score);
+        attempt.outcome = Some(outcome);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{storage, with_memory_storage};
+
+    #[test]
+    fn unversioned_profiles_upgrade_to_current() {
+        with_memory_storage(|| {
+            let file = serde_json::json!({ "moonrocks": 12, "cheddah": 3 });
+            storage().write(&slot_key(Profile::FILE, 2), &serde_json::to_vec(&file).unwrap()).unwrap();
+
+            let profile = Profile::load(2);
+            assert_eq!((profile.slot, profile.moonrocks, profile.cheddah), (2, 12, 3));
+            assert!(!profile.tampered);
+            assert!(profile.personal_bests.is_empty());
+        });
+    }
+
+    #[test]
+    fn profiles_round_trip() {
+        with_memory_storage(|| {
+            let mut profile = Profile { slot: 1, moonrocks: 5, ..Profile::default() };
+            profile.record_personal_best(GameMode::Classic, &SeedCode { seed: 42, modifiers: Vec::new() }, GameId::new(3, 42), 30);
+            profile.save().unwrap();
+
+            let loaded = Profile::load(1);
+            assert_eq!(loaded.moonrocks, 5);
+            assert_eq!(loaded.personal_bests, profile.personal_bests);
+            assert!(!loaded.tampered);
+        });
+    }
+}
@@ -0,0 +1,174 @@
+use std::io;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{slot_key, storage, Profile, SavedRun, Versioned};
+
+/// How many profiles one machine can hold.
+pub const PROFILE_SLOT_LIMIT: usize = 4;
+
+/// Longest name the rename box accepts, in characters.
+pub const PROFILE_NAME_LIMIT: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSlot {
+    pub id: u32,
+    pub name: String,
+}
+
+/// The profiles on this machine and which one is playing. There is always at least
+/// one slot and `active` always names one of them.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSlots {
+    pub slots: Vec<ProfileSlot>,
+    pub active: u32,
+    /// Ids are never reused, so a deleted profile's files can't leak into a new one.
+    pub next_id: u32,
+}
+
+impl Versioned for ProfileSlots {
+    const FILE: &'static str = "profiles.json";
+    const VERSION: u32 = 1;
+}
+
+impl ProfileSlots {
+    pub fn load() -> Self {
+        let mut slots: ProfileSlots = super::load_versioned()
+            .unwrap_or_else(|err| {
+                warn!("Could not read profile list, starting fresh: {}", err);
+                None
+            })
+            .unwrap_or_default();
+
+        if slots.slots.is_empty() {
+            let id = slots.create_default();
+            adopt_legacy_files(id);
+            if let Err(err) = slots.save() {
+                warn!("Could not save profile list: {}", err);
+            }
+        }
+        if !slots.slots.iter().any(|slot| slot.id == slots.active) {
+            slots.active = slots.slots[0].id;
+        }
+        slots
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::save_versioned(self)
+    }
+
+    pub fn active_slot(&self) -> &ProfileSlot {
+        self.slots.iter().find(|slot| slot.id == self.active).unwrap_or(&self.slots[0])
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.len() >= PROFILE_SLOT_LIMIT
+    }
+
+    /// Adds a slot and returns its id, or `None` when every slot is taken.
+    pub fn create(&mut self, name: &str) -> Option<u32> {
+        if self.is_full() {
+            return None;
+        }
+
+        self.next_id = self.next_id.max(1);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.slots.push(ProfileSlot { id, name: clean_name(name, id) });
+        Some(id)
+    }
+
+    fn create_default(&mut self) -> u32 {
+        let id = self.create("").expect("an empty profile list has room");
+        self.active = id;
+        id
+    }
+
+    pub fn rename(&mut self, id: u32, name: &str) -> bool {
+        let Some(slot) = self.slots.iter_mut().find(|slot| slot.id == id) else {
+            return false;
+        };
+
+        slot.name = clean_name(name, id);
+        true
+    }
+
+    pub fn select(&mut self, id: u32) -> bool {
+        if !self.slots.iter().any(|slot| slot.id == id) {
+            return false;
+        }
+
+        self.active = id;
+        true
+    }
+
+    /// Removes the slot and its profile and saved run. Deleting the last slot leaves a
+    /// fresh one behind; deleting the active slot selects another.
+    pub fn delete(&mut self, id: u32) -> io::Result<()> {
+        let Some(index) = self.slots.iter().position(|slot| slot.id == id) else {
+            return Ok(());
+        };
+
+        super::remove_file(&slot_key(Profile::FILE, id))?;
+        SavedRun::clear(id)?;
+        self.slots.remove(index);
+
+        if self.slots.is_empty() {
+            self.create_default();
+        } else if self.active == id {
+            self.active = self.slots[0].id;
+        }
+        Ok(())
+    }
+}
+
+fn clean_name(name: &str, id: u32) -> String {
+    let name: String = name.trim().chars().take(PROFILE_NAME_LIMIT).collect();
+    if name.is_empty() { format!("Player {}", id) } else { name }
+}
+
+/// Files from before profile slots become the first slot's.
+fn adopt_legacy_files(id: u32) {
+    let storage = storage();
+    for file in [Profile::FILE, SavedRun::FILE] {
+        if storage.exists(file)
+            && let Err(err) = storage.rename(file, &slot_key(file, id))
+        {
+            warn!("Could not move {} into profile {}: {}", file, id, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::with_memory_storage;
+
+    #[test]
+    fn single_profile_files_become_the_first_slot() {
+        with_memory_storage(|| {
+            let profile = serde_json::json!({ "moonrocks": 12 });
+            storage().write(Profile::FILE, &serde_json::to_vec(&profile).unwrap()).unwrap();
+            storage().write(SavedRun::FILE, b"{}").unwrap();
+
+            let slots = ProfileSlots::load();
+            assert_eq!(slots.slots, [ProfileSlot { id: 1, name: "Player 1".to_string() }]);
+            assert_eq!(slots.active, 1);
+            assert_eq!(Profile::load(1).moonrocks, 12);
+            assert!(SavedRun::exists(1));
+            assert!(!storage().exists(Profile::FILE));
+            assert!(!storage().exists(SavedRun::FILE));
+        });
+    }
+
+    #[test]
+    fn fresh_installs_get_one_empty_slot() {
+        with_memory_storage(|| {
+            let slots = ProfileSlots::load();
+            assert_eq!(slots.slots.len(), 1);
+            assert_eq!(Profile::load(slots.active).moonrocks, 0);
+        });
+    }
+}
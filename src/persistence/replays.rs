@@ -0,0 +1,211 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{GameId, Replay, RulesConfig};
+use super::{LoadError, Versioned};
+
+/// Replays that aren't pinned are deleted past this many, oldest first.
+pub const REPLAY_LIMIT: usize = 100;
+
+/// `FILE` is only a fallback; each replay is saved under [`replay_key`].
+impl Versioned for Replay {
+    const FILE: &'static str = "replay.json";
+    const VERSION: u32 = Replay::FORMAT_VERSION;
+    const PRETTY: bool = false;
+}
+
+/// The saved replays, oldest first. Storage can't list its keys, so this is how
+/// old replays are found when pruning.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayIndex {
+    pub entries: Vec<ReplayIndexEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayIndexEntry {
+    pub game_id: GameId,
+    /// Kept past [`REPLAY_LIMIT`], e.g. a personal best that a ghost races.
+    pub pinned: bool,
+}
+
+impl Versioned for ReplayIndex {
+    const FILE: &'static str = "replays.json";
+    const VERSION: u32 = 1;
+}
+
+impl ReplayIndex {
+    pub fn load() -> Self {
+        super::load_versioned()
+            .unwrap_or_else(|err| {
+                warn!("Could not read replay index, starting a new one: {}", err);
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::save_versioned(self)
+    }
+
+    /// Deletes the oldest unpinned replays until at most [`REPLAY_LIMIT`] are left.
+    fn prune(&mut self) {
+        let mut excess = self.entries.iter().filter(|entry| !entry.pinned).count().saturating_sub(REPLAY_LIMIT);
+        self.entries.retain(|entry| {
+            if excess == 0 || entry.pinned {
+                return true;
+            }
+            match super::remove_file(&replay_key(entry.game_id)) {
+                Ok(()) => {
+                    excess -= 1;
+                    false
+                }
+                Err(err) => {
+                    warn!("Could not delete old replay of game {}: {}", entry.game_id, err);
+                    true
+                }
+            }
+        });
+    }
+}
+
+/// One replay per finished run, named after its game ID, e.g. `replay-42-9F3A1C2B.json`.
+pub fn replay_key(game_id: GameId) -> String {
+    format!("replay-{}.json", game_id)
+}
+
+/// Each set of rules replays were recorded under is saved once, named by its fingerprint.
+pub fn rules_key(rules_hash: u32) -> String {
+    format!("rules-{:08X}.json", rules_hash)
+}
+
+/// Saves `replay` along with the `rules` it was recorded under, then prunes old replays.
+pub fn save_replay(replay: &Replay, rules: &RulesConfig) -> io::Result<()> {
+    save_rules(rules)?;
+    super::save_versioned_to(&replay_key(replay.game_id), replay)?;
+
+    let mut index = ReplayIndex::load();
+    if !index.entries.iter().any(|entry| entry.game_id == replay.game_id) {
+        index.entries.push(ReplayIndexEntry { game_id: replay.game_id, pinned: false });
+    }
+    index.prune();
+    index.save()
+}
+
+/// Keeps `game_id` past the limit, and lets `replaces` be pruned again.
+pub fn pin_replay(game_id: GameId, replaces: Option<GameId>) -> io::Result<()> {
+    let mut index = ReplayIndex::load();
+    for entry in &mut index.entries {
+        if entry.game_id == game_id {
+            entry.pinned = true;
+        } else if Some(entry.game_id) == replaces {
+            entry.pinned = false;
+        }
+    }
+    index.prune();
+    index.save()
+}
+
+pub fn replay_exists(game_id: GameId) -> bool {
+    super::file_exists(&replay_key(game_id))
+}
+
+pub fn load_replay(game_id: GameId) -> Result<Option<Replay>, LoadError> {
+    super::load_versioned_from(&replay_key(game_id))
+}
+
+/// The rules saved for `rules_hash` by [`save_replay`].
+pub fn load_rules(rules_hash: u32) -> Result<Option<RulesConfig>, LoadError> {
+    let key = rules_key(rules_hash);
+    let Some(contents) = super::storage().read(&key).map_err(|err| LoadError::Io(key.clone(), err))? else {
+        return Ok(None);
+    };
+    serde_json::from_slice(&contents)
+        .map(Some)
+        .map_err(|err| LoadError::Corrupt { file: key, reason: err.to_string(), backup: None })
+}
+
+/// Reads a replay file shared outside the game, e.g. attached to a bug report.
+pub fn read_replay_file(path: impl AsRef<Path>) -> Result<Replay, LoadError> {
+    let path = path.as_ref();
+    let name = path.display().to_string();
+    let contents = fs::read(path).map_err(|err| LoadError::Io(name.clone(), err))?;
+    super::parse_versioned(&name, &contents)
+}
+
+fn save_rules(rules: &RulesConfig) -> io::Result<()> {
+    let key = rules_key(rules.fingerprint());
+    if super::file_exists(&key) {
+        return Ok(());
+    }
+    super::write_json(&key, rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::with_memory_storage;
+    use crate::rules::{GameMode, ReplayAction, ReplayMismatch, RunState, VerifyError};
+
+    fn finished_replay(rules: &RulesConfig, run_number: u64) -> Replay {
+        let mut state = RunState::new_run(rules, GameMode::Classic, run_number, run_number);
+        let mut replay = Replay::new(rules, &state);
+        while state.can_pull() {
+            state.apply(ReplayAction::Pull, rules);
+            replay.record(ReplayAction::Pull, &state);
+        }
+        replay.finish(&state);
+        replay
+    }
+
+    #[test]
+    fn saved_replays_load_and_verify() {
+        with_memory_storage(|| {
+            let rules = RulesConfig::default();
+            let replay = finished_replay(&rules, 1);
+            save_replay(&replay, &rules).unwrap();
+
+            let loaded = load_replay(replay.game_id).unwrap().unwrap();
+            assert_eq!(loaded, replay);
+            let saved_rules = load_rules(loaded.rules_hash).unwrap().unwrap();
+            assert_eq!(loaded.verify(&saved_rules), Ok(replay.result.unwrap()));
+        });
+    }
+
+    #[test]
+    fn diverging_replays_fail_verification() {
+        with_memory_storage(|| {
+            let rules = RulesConfig::default();
+            let mut replay = finished_replay(&rules, 1);
+            replay.digests[0] ^= 1;
+            save_replay(&replay, &rules).unwrap();
+
+            let loaded = load_replay(replay.game_id).unwrap().unwrap();
+            assert!(matches!(loaded.verify(&rules), Err(VerifyError::Diverged(ReplayMismatch { step: 0, .. }))));
+        });
+    }
+
+    #[test]
+    fn old_replays_are_pruned_but_pinned_ones_kept() {
+        with_memory_storage(|| {
+            let rules = RulesConfig::default();
+            let replays: Vec<Replay> =
+                (1..=REPLAY_LIMIT as u64 + 2).map(|run| Replay::new(&rules, &RunState::new_run(&rules, GameMode::Classic, run, run))).collect();
+
+            save_replay(&replays[0], &rules).unwrap();
+            pin_replay(replays[0].game_id, None).unwrap();
+            for replay in &replays[1..] {
+                save_replay(replay, &rules).unwrap();
+            }
+
+            assert!(replay_exists(replays[0].game_id));
+            assert!(!replay_exists(replays[1].game_id));
+            assert!(replays[2..].iter().all(|replay| replay_exists(replay.game_id)));
+            assert_eq!(ReplayIndex::load().entries.len(), REPLAY_LIMIT + 1);
+        });
+    }
+}
@@ -0,0 +1,37 @@
+use std::io;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::Versioned;
+
+/// Monotonic count of runs started on this machine; the first half of every `GameId`.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunCounter {
+    pub last_run: u64,
+}
+
+impl Versioned for RunCounter {
+    const FILE: &'static str = "run_counter.json";
+    const VERSION: u32 = 1;
+}
+
+impl RunCounter {
+    pub fn load() -> Self {
+        super::load_versioned()
+            .unwrap_or_else(|err| {
+                warn!("Could not read run counter, starting from zero: {}", err);
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::save_versioned(self)
+    }
+
+    pub fn next_run(&mut self) -> u64 {
+        self.last_run += 1;
+        self.last_run
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::rules::{BossModifier, GameId, GameMode, Orb, RulesConfig, RunOutcome, RunState};
+use super::signing;
+
+const RUN_HISTORY_FILE: &str = "run_history.jsonl";
+
+/// Everything needed to study a finished run offline: the rules it was played under,
+/// the seed, every orb drawn and how it ended. One JSON object per line in
+/// `run_history.jsonl`; lines are only ever appended, each with its own signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub game_id: GameId,
+    pub mode: GameMode,
+    pub seed: u64,
+    pub modifiers: Vec<BossModifier>,
+    pub config: RulesConfig,
+    pub pulls: Vec<Orb>,
+    pub outcome: RunOutcome,
+    pub level: u32,
+    pub score: u32,
+    pub health: u32,
+    pub moonrocks: u32,
+    pub cheddah: u32,
+    /// Local time the run ended, RFC 3339.
+    pub finished_at: String,
+    /// Whether the line's signature checked out when it was loaded. Not saved.
+    #[serde(skip)]
+    pub verified: bool,
+}
+
+impl RunRecord {
+    pub fn new(state: &RunState, rules: &RulesConfig, outcome: RunOutcome) -> Self {
+        Self {
+            game_id: state.game_id,
+            mode: state.mode,
+            seed: state.seed,
+            modifiers: state.modifiers.clone(),
+            config: rules.clone(),
+            pulls: state.pulls.clone(),
+            outcome,
+            level: state.level,
+            score: state.total_score(),
+            health: state.health,
+            moonrocks: state.moonrocks,
+            cheddah: state.cheddah,
+            finished_at: Local::now().to_rfc3339(),
+            verified: true,
+        }
+    }
+
+    pub fn append(&self) -> io::Result<()> {
+        let Value::Object(mut data) =
+            serde_json::to_value(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+        else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "run record must be a JSON object"));
+        };
+
+        signing::sign(&mut data);
+        super::append_json_line(RUN_HISTORY_FILE, &data)
+    }
+
+    /// Every run recorded on this machine, oldest first.
+    pub fn load_all() -> io::Result<Vec<RunRecord>> {
+        match super::storage().read(RUN_HISTORY_FILE)? {
+            Some(bytes) => Self::read_from(bytes.as_slice()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Loads a history file copied off a player's machine.
+    pub fn load_from(path: impl AsRef<Path>) -> io::Result<Vec<RunRecord>> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Parses JSON Lines, skipping blank lines. A bad line fails the whole read and
+    /// the error names its line number; a line that parses but fails its signature is
+    /// kept with `verified: false`.
+    pub fn read_from(reader: impl BufRead) -> io::Result<Vec<RunRecord>> {
+        let mut records = Vec::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let invalid = |err: serde_json::Error| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", index + 1, err))
+            };
+
+            let mut data: Map<String, Value> = serde_json::from_str(&line).map_err(invalid)?;
+            let verified = signing::verify(&mut data);
+            let mut record: RunRecord = serde_json::from_value(Value::Object(data)).map_err(invalid)?;
+            record.verified = verified;
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+}
@@ -0,0 +1,66 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{Replay, RunState};
+use super::{slot_key, LoadError, Versioned};
+
+/// The run in progress, written after every pull so it can be continued after quitting
+/// or a crash. Each profile slot has at most one; finishing the run deletes it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedRun {
+    pub run: RunState,
+    /// The recording so far, so a resumed run still produces a complete replay.
+    pub replay: Option<Replay>,
+}
+
+impl Versioned for SavedRun {
+    const FILE: &'static str = "saved_run.json";
+    const VERSION: u32 = 1;
+}
+
+impl SavedRun {
+    fn key(slot: u32) -> String {
+        slot_key(Self::FILE, slot)
+    }
+
+    pub fn exists(slot: u32) -> bool {
+        super::file_exists(&Self::key(slot))
+    }
+
+    pub fn load(slot: u32) -> Result<Option<SavedRun>, LoadError> {
+        super::load_versioned_from(&Self::key(slot))
+    }
+
+    pub fn save(slot: u32, run: &RunState, replay: Option<&Replay>) -> io::Result<()> {
+        super::save_versioned_to(&Self::key(slot), &SavedRun { run: run.clone(), replay: replay.cloned() })
+    }
+
+    pub fn clear(slot: u32) -> io::Result<()> {
+        super::remove_file(&Self::key(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::with_memory_storage;
+    use crate::rules::{GameMode, ReplayAction, RulesConfig};
+
+    #[test]
+    fn saved_runs_keep_their_replay() {
+        with_memory_storage(|| {
+            let rules = RulesConfig::default();
+            let mut run = RunState::new_run(&rules, GameMode::Classic, 3, 42);
+            let mut replay = Replay::new(&rules, &run);
+            run.apply(ReplayAction::Pull, &rules);
+            replay.record(ReplayAction::Pull, &run);
+
+            SavedRun::save(1, &run, Some(&replay)).unwrap();
+            assert_eq!(SavedRun::load(1).unwrap(), Some(SavedRun { run, replay: Some(replay) }));
+
+            SavedRun::clear(1).unwrap();
+            assert!(!SavedRun::exists(1));
+        });
+    }
+}
@@ -0,0 +1,58 @@
+//! HMAC-SHA256 over saved data, so hand-edited profiles, score tables and run records
+//! can be spotted. The key ships inside the binary, so this is tamper evidence, not
+//! security: anyone willing to dig the key out can still forge a file.
+
+use hmac::{Hmac, Mac};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+/// Release builds should set `GLITCHBOMB_SIGNING_KEY` at compile time.
+const SIGNING_KEY: &[u8] = match option_env!("GLITCHBOMB_SIGNING_KEY") {
+    Some(key) => key.as_bytes(),
+    None => b"glitchbomb-dev-signing-key",
+};
+
+/// The key a signature is stored under, next to the fields it covers.
+pub const SIGNATURE_KEY: &str = "signature";
+
+fn mac_for(data: &Map<String, Value>) -> Hmac<Sha256> {
+    // serde_json maps are sorted, so the compact form is the same wherever it's computed.
+    let payload = Value::Object(data.clone()).to_string();
+    let mut mac = Hmac::<Sha256>::new_from_slice(SIGNING_KEY).expect("HMAC accepts any key length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Adds a signature over every other field of `data`.
+pub fn sign(data: &mut Map<String, Value>) {
+    data.remove(SIGNATURE_KEY);
+    let signature = mac_for(data).finalize().into_bytes();
+    data.insert(SIGNATURE_KEY.to_string(), to_hex(&signature).into());
+}
+
+/// Removes the signature from `data` and checks it. `false` if it's missing or doesn't match.
+pub fn verify(data: &mut Map<String, Value>) -> bool {
+    let Some(signature) = data.remove(SIGNATURE_KEY) else {
+        return false;
+    };
+
+    signature
+        .as_str()
+        .and_then(from_hex)
+        .is_some_and(|signature| mac_for(data).verify_slice(&signature).is_ok())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
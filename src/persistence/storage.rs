@@ -0,0 +1,273 @@
+//! Where saved bytes actually live. Everything else in `persistence` goes through
+//! [`storage`], so save, profile and settings code never touches the filesystem itself.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+/// A flat key-value store of small blobs. Keys are file-like names such as `profile.json`.
+pub trait Storage: Send + Sync {
+    /// `Ok(None)` when nothing is stored under `key`.
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    /// Replaces the whole value. Readers must never see a half-written one.
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+    fn append(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+    /// Removing a missing key is not an error.
+    fn remove(&self, key: &str) -> io::Result<()>;
+    fn rename(&self, from: &str, to: &str) -> io::Result<()>;
+    /// Where `key` lives, for log and error messages.
+    fn locate(&self, key: &str) -> String;
+
+    fn exists(&self, key: &str) -> bool {
+        matches!(self.read(key), Ok(Some(_)))
+    }
+}
+
+static STORAGE: RwLock<Option<Arc<dyn Storage>>> = RwLock::new(None);
+
+/// The active backend: whatever was last passed to [`set_storage`], otherwise
+/// localStorage on the web and the data directory everywhere else.
+pub fn storage() -> Arc<dyn Storage> {
+    if let Some(storage) = STORAGE.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+        return storage.clone();
+    }
+
+    let mut slot = STORAGE.write().unwrap_or_else(PoisonError::into_inner);
+    slot.get_or_insert_with(default_storage).clone()
+}
+
+/// Swaps the backend for the rest of the process, e.g. to `MemoryStorage` in tests and tools.
+pub fn set_storage(storage: impl Storage + 'static) {
+    *STORAGE.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(storage));
+}
+
+/// Runs `test` against a fresh [`MemoryStorage`]. The backend is global, so tests that
+/// touch storage take turns.
+#[cfg(test)]
+pub(crate) fn with_memory_storage<R>(test: impl FnOnce() -> R) -> R {
+    static TURN: Mutex<()> = Mutex::new(());
+    let _turn = TURN.lock().unwrap_or_else(PoisonError::into_inner);
+    set_storage(MemoryStorage::new());
+    test()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_storage() -> Arc<dyn Storage> {
+    Arc::new(FileStorage::new(super::data_dir()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn default_storage() -> Arc<dyn Storage> {
+    Arc::new(WebStorage::new("glitchbomb/"))
+}
+
+/// One file per key in a directory. `None` is a platform with no data directory:
+/// reads find nothing and writes fail.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    dir: Option<PathBuf>,
+}
+
+impl FileStorage {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(key))
+    }
+
+    fn ensure_dir(&self) -> io::Result<&PathBuf> {
+        let dir = self
+            .dir
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory on this platform"))?;
+        fs::create_dir_all(dir)?;
+        Ok(dir)
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        let Some(path) = self.path(key) else {
+            return Ok(None);
+        };
+
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes through a temporary file so a crash mid-write can't leave a truncated save.
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let dir = self.ensure_dir()?;
+        let tmp_path = dir.join(format!("{}.tmp", key));
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, dir.join(key))
+    }
+
+    fn append(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let dir = self.ensure_dir()?;
+        // A single write per call keeps appended lines whole even if two runs end at once.
+        let mut file = OpenOptions::new().create(true).append(true).open(dir.join(key))?;
+        file.write_all(bytes)
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        let Some(path) = self.path(key) else {
+            return Ok(());
+        };
+
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let dir = self.ensure_dir()?;
+        fs::rename(dir.join(from), dir.join(to))
+    }
+
+    fn locate(&self, key: &str) -> String {
+        match self.path(key) {
+            Some(path) => path.display().to_string(),
+            None => key.to_string(),
+        }
+    }
+
+    fn exists(&self, key: &str) -> bool {
+        self.path(key).is_some_and(|path| path.is_file())
+    }
+}
+
+/// Keeps everything in memory; nothing survives the process.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<u8>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.entries().get(key).cloned())
+    }
+
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.entries().insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn append(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.entries().entry(key.to_string()).or_default().extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        self.entries().remove(key);
+        Ok(())
+    }
+
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let mut entries = self.entries();
+        let bytes = entries
+            .remove(from)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} not found", from)))?;
+        entries.insert(to.to_string(), bytes);
+        Ok(())
+    }
+
+    fn locate(&self, key: &str) -> String {
+        format!("memory:{}", key)
+    }
+}
+
+/// Browser localStorage, for the web build. Values are stored as strings, so they
+/// must be UTF-8; everything the game saves is JSON.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone)]
+pub struct WebStorage {
+    prefix: &'static str,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WebStorage {
+    /// `prefix` namespaces keys so other pages on the same origin (e.g. itch.io) can't clash.
+    pub fn new(prefix: &'static str) -> Self {
+        Self { prefix }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    fn local_storage(&self) -> io::Result<web_sys::Storage> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "localStorage is not available"))
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<String>> {
+        self.local_storage()?.get_item(&self.key(key)).map_err(js_error)
+    }
+
+    fn set(&self, key: &str, value: &str) -> io::Result<()> {
+        self.local_storage()?.set_item(&self.key(key), value).map_err(js_error)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn js_error(err: wasm_bindgen::JsValue) -> io::Error {
+    io::Error::other(format!("localStorage: {:?}", err))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn utf8(bytes: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for WebStorage {
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.get(key)?.map(String::into_bytes))
+    }
+
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.set(key, utf8(bytes)?)
+    }
+
+    fn append(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let mut value = self.get(key)?.unwrap_or_default();
+        value.push_str(utf8(bytes)?);
+        self.set(key, &value)
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        self.local_storage()?.remove_item(&self.key(key)).map_err(js_error)
+    }
+
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let value = self
+            .get(from)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} not found", from)))?;
+        self.set(to, &value)?;
+        self.remove(from)
+    }
+
+    fn locate(&self, key: &str) -> String {
+        format!("localStorage:{}", self.key(key))
+    }
+}
@@ -0,0 +1,292 @@
+use std::fmt;
+use std::io;
+
+use bevy::prelude::*;
+use chrono::Local;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use super::signing;
+use super::storage::{storage, Storage};
+
+/// A type saved as one JSON file in storage, with a top-level `version`
+/// key next to its own fields. Files older than the current version are upgraded one
+/// step at a time through [`Versioned::migrate`] before they're deserialized.
+pub trait Versioned: Serialize + DeserializeOwned {
+    const FILE: &'static str;
+    const VERSION: u32;
+    /// First version saved with a signature. Files from that version on must carry a
+    /// valid one. Older files predate signing and are trusted once, then signed on save,
+    /// unless this install has already signed the file: see [`signed_marker_key`].
+    const SIGNED_SINCE: Option<u32> = None;
+    /// Indented for reading by hand. Files that are large or numerous turn this off.
+    const PRETTY: bool = true;
+
+    /// Upgrades `data` from version `from` to `from + 1`. Version 0 is a file written
+    /// before versions existed. The default suits bumps that didn't change the shape.
+    fn migrate(from: u32, data: &mut Map<String, Value>) -> Result<(), String> {
+        let _ = (from, data);
+        Ok(())
+    }
+
+    /// Called when a signed file fails verification. The data is still loaded, so types
+    /// that sign should record the mark in a saved field to keep it after the next save.
+    fn mark_tampered(&mut self) {}
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(String, io::Error),
+    /// Not valid JSON, not the expected shape, or a migration failed.
+    /// `backup` says where the file was moved, if it could be.
+    Corrupt { file: String, reason: String, backup: Option<String> },
+    /// Written by a newer build than this one.
+    UnknownVersion { file: String, found: u32, supported: u32, backup: Option<String> },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(file, err) => write!(f, "could not read {}: {}", file, err),
+            LoadError::Corrupt { file, reason, backup } => {
+                write!(f, "{} is corrupted ({})", file, reason)?;
+                write_backup(f, backup)
+            }
+            LoadError::UnknownVersion { file, found, supported, backup } => {
+                write!(f, "{} is version {}, but this build only understands up to {}", file, found, supported)?;
+                write_backup(f, backup)
+            }
+        }
+    }
+}
+
+fn write_backup(f: &mut fmt::Formatter<'_>, backup: &Option<String>) -> fmt::Result {
+    match backup {
+        Some(location) => write!(f, "; moved aside to {}", location),
+        None => Ok(()),
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+enum Invalid {
+    Corrupt(String),
+    UnknownVersion(u32),
+}
+
+/// Loads and upgrades `T`. `Ok(None)` means nothing is saved yet. A file that can't be
+/// understood is renamed out of the way first, so the next save can't overwrite it.
+pub fn load_versioned<T: Versioned>() -> Result<Option<T>, LoadError> {
+    load_versioned_from(T::FILE)
+}
+
+pub fn save_versioned<T: Versioned>(value: &T) -> io::Result<()> {
+    save_versioned_to(T::FILE, value)
+}
+
+/// [`load_versioned`] from a key other than `T::FILE`, e.g. one file per profile slot.
+pub fn load_versioned_from<T: Versioned>(key: &str) -> Result<Option<T>, LoadError> {
+    let storage = storage();
+    let Some(contents) = storage.read(key).map_err(|err| LoadError::Io(key.to_string(), err))? else {
+        return Ok(None);
+    };
+
+    let signed_before = T::SIGNED_SINCE.is_some() && storage.exists(&signed_marker_key(key));
+    match upgrade::<T>(key, &contents, signed_before) {
+        Ok(value) => Ok(Some(value)),
+        Err(Invalid::Corrupt(reason)) => {
+            Err(LoadError::Corrupt { file: key.to_string(), reason, backup: back_up(&*storage, key) })
+        }
+        Err(Invalid::UnknownVersion(found)) => Err(LoadError::UnknownVersion {
+            file: key.to_string(),
+            found,
+            supported: T::VERSION,
+            backup: back_up(&*storage, key),
+        }),
+    }
+}
+
+/// Parses and upgrades `T` from bytes that didn't come from storage, such as a file a
+/// player shared. Nothing is moved aside on failure.
+pub fn parse_versioned<T: Versioned>(name: &str, contents: &[u8]) -> Result<T, LoadError> {
+    upgrade::<T>(name, contents, false).map_err(|invalid| match invalid {
+        Invalid::Corrupt(reason) => LoadError::Corrupt { file: name.to_string(), reason, backup: None },
+        Invalid::UnknownVersion(found) => {
+            LoadError::UnknownVersion { file: name.to_string(), found, supported: T::VERSION, backup: None }
+        }
+    })
+}
+
+pub fn save_versioned_to<T: Versioned>(key: &str, value: &T) -> io::Result<()> {
+    let Value::Object(mut data) =
+        serde_json::to_value(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+    else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "versioned data must be a JSON object"));
+    };
+
+    data.insert("version".to_string(), T::VERSION.into());
+    if T::SIGNED_SINCE.is_some() {
+        signing::sign(&mut data);
+    }
+    if T::PRETTY {
+        super::write_json(key, &data)?;
+    } else {
+        super::write_json_compact(key, &data)?;
+    }
+
+    // Written after the file, so a failed save can't make an old file look tampered.
+    let marker = signed_marker_key(key);
+    if T::SIGNED_SINCE.is_some() && !super::file_exists(&marker) {
+        storage().write(&marker, &[])?;
+    }
+    Ok(())
+}
+
+/// Left next to a signed file once this install has written it. From then on an
+/// unsigned copy can't be an old save from before signing, so it's treated as tampered:
+/// otherwise rolling the version back and deleting the signature would pass.
+pub fn signed_marker_key(key: &str) -> String {
+    format!("{}.signed", key)
+}
+
+fn upgrade<T: Versioned>(key: &str, contents: &[u8], signed_before: bool) -> Result<T, Invalid> {
+    let mut data: Map<String, Value> =
+        serde_json::from_slice(contents).map_err(|err| Invalid::Corrupt(err.to_string()))?;
+
+    // The signature covers the file as written, so check it before anything is changed.
+    let verified = signing::verify(&mut data);
+
+    let version = match data.remove("version") {
+        None => 0,
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| Invalid::Corrupt(format!("invalid version {}", value)))?,
+    };
+    if version > T::VERSION {
+        return Err(Invalid::UnknownVersion(version));
+    }
+    let tampered = T::SIGNED_SINCE.is_some_and(|since| version >= since || signed_before) && !verified;
+
+    for from in version..T::VERSION {
+        T::migrate(from, &mut data)
+            .map_err(|reason| Invalid::Corrupt(format!("upgrading from version {}: {}", from, reason)))?;
+    }
+
+    let mut value: T =
+        serde_json::from_value(Value::Object(data)).map_err(|err| Invalid::Corrupt(err.to_string()))?;
+    if tampered {
+        warn!("{} failed its integrity check and may have been edited by hand", key);
+        value.mark_tampered();
+    }
+    Ok(value)
+}
+
+fn back_up(storage: &dyn Storage, key: &str) -> Option<String> {
+    let backup = format!("{}.corrupt-{}", key, Local::now().format("%Y%m%dT%H%M%S"));
+
+    match storage.rename(key, &backup) {
+        Ok(()) => Some(storage.locate(&backup)),
+        Err(err) => {
+            warn!("Could not back up {}: {}", storage.locate(key), err);
+            None
+        }
+    }
+}
+
+/// The per-slot key for `file`, e.g. `profile.json` in slot 2 is `profile-2.json`.
+pub fn slot_key(file: &str, slot: u32) -> String {
+    match file.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}-{}.{}", stem, slot, extension),
+        None => format!("{}-{}", file, slot),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{with_memory_storage, Profile};
+
+    const KEY: &str = "profile-1.json";
+
+    fn profile(moonrocks: u32) -> Profile {
+        Profile { moonrocks, ..Profile::default() }
+    }
+
+    fn load_profile() -> Profile {
+        load_versioned_from::<Profile>(KEY).unwrap().unwrap()
+    }
+
+    /// Rewrites the stored file as an older, unsigned version with `edit` applied.
+    fn rewrite(version: u32, edit: impl FnOnce(&mut Map<String, Value>)) {
+        let mut data: Map<String, Value> = serde_json::from_slice(&storage().read(KEY).unwrap().unwrap()).unwrap();
+        data.remove(signing::SIGNATURE_KEY);
+        data.insert("version".to_string(), version.into());
+        edit(&mut data);
+        storage().write(KEY, &serde_json::to_vec(&data).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn signed_files_round_trip() {
+        with_memory_storage(|| {
+            save_versioned_to(KEY, &profile(7)).unwrap();
+            let loaded = load_profile();
+            assert_eq!(loaded.moonrocks, 7);
+            assert!(!loaded.tampered);
+        });
+    }
+
+    #[test]
+    fn edited_signed_files_are_marked_tampered() {
+        with_memory_storage(|| {
+            save_versioned_to(KEY, &profile(7)).unwrap();
+            let mut data: Map<String, Value> = serde_json::from_slice(&storage().read(KEY).unwrap().unwrap()).unwrap();
+            data.insert("moonrocks".to_string(), 9999.into());
+            storage().write(KEY, &serde_json::to_vec(&data).unwrap()).unwrap();
+
+            let loaded = load_profile();
+            assert_eq!(loaded.moonrocks, 9999);
+            assert!(loaded.tampered);
+        });
+    }
+
+    #[test]
+    fn unsigned_files_from_before_signing_are_trusted_once() {
+        with_memory_storage(|| {
+            let data = serde_json::json!({ "version": 1, "moonrocks": 7 });
+            storage().write(KEY, &serde_json::to_vec(&data).unwrap()).unwrap();
+
+            let loaded = load_profile();
+            assert!(!loaded.tampered);
+            save_versioned_to(KEY, &loaded).unwrap();
+            assert!(storage().exists(&signed_marker_key(KEY)));
+            assert!(!load_profile().tampered);
+        });
+    }
+
+    #[test]
+    fn downgraded_and_stripped_files_are_marked_tampered() {
+        with_memory_storage(|| {
+            save_versioned_to(KEY, &profile(7)).unwrap();
+            rewrite(1, |data| {
+                data.insert("moonrocks".to_string(), 9999.into());
+            });
+
+            let loaded = load_profile();
+            assert!(loaded.tampered);
+
+            // Saving keeps the mark rather than laundering the edit.
+            save_versioned_to(KEY, &loaded).unwrap();
+            assert!(load_profile().tampered);
+        });
+    }
+
+    #[test]
+    fn stripping_the_signature_alone_is_caught() {
+        with_memory_storage(|| {
+            save_versioned_to(KEY, &profile(7)).unwrap();
+            rewrite(Profile::VERSION, |_| {});
+            assert!(load_profile().tampered);
+        });
+    }
+}